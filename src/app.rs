@@ -1,63 +1,889 @@
-use crate::sort::*;
+use std::{
+    mem,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-#[derive(PartialEq)]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    cli::Options,
+    config::{Config, Overrides, SPEEDS},
+    input::{Dataset, Distribution},
+    sort::*,
+    theme::Theme,
+    worker::{Command, Worker},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum AppScreen {
     Menu,
     Sort,
+    Settings,
+    Options, // params of the selected algorithm
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Setting {
+    Algorithm,
+    Speed,
+    Length,
+    Distribution,
+    View,
+    Theme,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 6] = [
+        Setting::Algorithm,
+        Setting::Speed,
+        Setting::Length,
+        Setting::Distribution,
+        Setting::View,
+        Setting::Theme,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Setting::Algorithm => "Default algorithm",
+            Setting::Speed => "Speed",
+            Setting::Length => "Array size",
+            Setting::Distribution => "Distribution",
+            Setting::View => "View",
+            Setting::Theme => "Theme",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum View {
+    Bars,
+    Dots,
+}
+
+impl View {
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Bars => "bars",
+            View::Dots => "dots",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<View> {
+        match name {
+            "bars" => Some(View::Bars),
+            "dots" => Some(View::Dots),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> View {
+        match self {
+            View::Bars => View::Dots,
+            View::Dots => View::Bars,
+        }
+    }
+}
+
+// how long the finished array stays on screen in kiosk mode before the next algorithm starts
+const KIOSK_PAUSE: Duration = Duration::from_secs(3);
+// algorithms expected to take longer than this are skipped in kiosk mode
+const KIOSK_TIME_LIMIT: Duration = Duration::from_secs(180);
+
+pub const DEFAULT_SPEED: u32 = 125;
+// selected when none is configured, rather than whichever comes first alphabetically
+const DEFAULT_ALGORITHM: &str = "Quicksort";
+pub const FPS: u32 = 60;
+// how many frames the green completion sweep takes to cross the data
+const SWEEP_FRAMES: usize = FPS as usize;
+
+#[derive(Clone, Copy)]
+pub enum Complexity {
+    Linear,
+    Linearithmic,
+    Quadratic,
+    Power(f64), // n to this power, e.g. stooge sort's log 3 / log 1.5
+    Superpolynomial, // slowsort's n^(log n / (2 + ε)), which grows faster than any power
+    Factorial,
+}
+
+impl Complexity {
+    /// A rough estimate of the number of steps needed to sort `n` elements.
+    pub fn expected_steps(self, n: usize) -> f64 {
+        let n = n as f64;
+        match self {
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.log2().max(1.0),
+            Complexity::Quadratic => n * n / 2.0,
+            Complexity::Power(power) => n.powf(power),
+            Complexity::Superpolynomial => {
+                // slowsort's comparisons, as it sorts both halves, compares, then sorts all but one
+                let mut comparisons = vec![0.0; n as usize + 1];
+                for len in 2..comparisons.len() {
+                    comparisons[len] = comparisons[len.div_ceil(2)] + comparisons[len / 2]
+                        + comparisons[len - 1] + 1.0;
+                }
+                comparisons.last().copied().unwrap_or(0.0)
+            }
+            Complexity::Factorial => (1..=n as u64).map(|i| i as f64).product(),
+        }
+    }
 }
 
 pub struct Algorithm {
     pub name: &'static str,
-    pub new: fn() -> Box<dyn Sort<usize>>,
+    pub params: &'static [Param],
+    // takes the chosen value of each param, and a random number generator for sorts that need one
+    pub new: fn(&[Value], StdRng) -> Box<dyn Sort<usize>>,
+    pub complexity: Complexity,
+    pub speed: u32, // default steps per second
+}
+
+pub struct Stats {
+    pub steps: u64,
+    pub comparisons: u64,
+    pub writes: u64, // elements written to the array, two for each swap
+    elapsed: Duration, // running time up to the last pause
+    resumed: Option<Instant>, // None while paused or done
+}
+
+impl Stats {
+    fn new() -> Stats {
+        Stats {
+            steps: 0,
+            comparisons: 0,
+            writes: 0,
+            elapsed: Duration::ZERO,
+            resumed: Some(Instant::now()),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.resumed.map_or(Duration::ZERO, |resumed| resumed.elapsed())
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(resumed) = self.resumed.take() {
+            self.elapsed += resumed.elapsed();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.resumed.get_or_insert_with(Instant::now);
+    }
 }
 
 pub struct App {
-    pub data: [usize; crate::ARRAY_LEN],
+    pub data: Vec<usize>,
+    pub dataset: Dataset,
+    pub custom: bool, // user-supplied data is sorted in the order it was given
+    pub rng: StdRng,
+    pub config: Config, // what's saved: the config file, plus changes made on the settings screen
+    pub config_path: Option<PathBuf>,
+    pub overrides: Overrides, // take precedence over `config` for this run only
+    pub setting: usize, // selected row of the settings screen
+    pub message: Option<String>, // shown in the footer, e.g. after saving the config
+    pub help: bool, // whether the key binding overlay is shown
+    pub paused: bool,
+    pub step_once: bool, // advance a paused sort by one step
+    pub kiosk: bool,
+    pub kiosk_pause: Option<Instant>, // when the current pause between kiosk runs ends
     pub current_screen: AppScreen,
     pub algorithms: Vec<Algorithm>,
     pub selected: usize,
-    pub sort: Option<Box<dyn Sort<usize>>>,
-    pub swapped: Vec<usize>,
+    pub params: Vec<Vec<Value>>, // chosen value of each param, for every algorithm
+    pub param: usize, // param selected in the options panel
+    pub worker: Option<Worker>, // None once the sort is done
+    pub annotations: Annotations, // from the latest batch of steps
+    pub swapped: Vec<bool>, // indices swapped by the latest batch of steps
+    pub compared: Vec<bool>, // indices compared by the latest batch of steps
+    pending: f64, // steps due but not yet run, carried over to the next frame
+    pub stats: Stats,
     pub n: usize, // for making the green "finished" color sweep across the data
+    pub abandoned: bool, // the sort gave up, so there's no sweep
+    pub expected_steps: f64, // by the running sort, worked out once when it starts
+    pub cursor: Option<usize>, // bar whose original value is shown in the footer
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(
+        config: Config,
+        config_path: Option<PathBuf>,
+        options: &Options,
+        dataset: Option<Dataset>,
+    ) -> Result<App, String> {
         let algorithms: Vec<Algorithm> = vec![
+                Algorithm {
+                    name: "Bogobogosort",
+                    params: &[BUDGET],
+                    new: |params, rng| Box::new(Budget::new(
+                        BogoBogoSort::new(rng),
+                        BUDGETS[params[0].choice()],
+                    )),
+                    complexity: Complexity::Factorial,
+                    speed: DEFAULT_SPEED,
+                },
                 Algorithm {
                     name: "Bogosort",
-                    new: || Box::new(BogoSort::new())
+                    params: &[BUDGET],
+                    new: |params, rng| Box::new(Budget::new(
+                        BogoSort::new(rng),
+                        BUDGETS[params[0].choice()],
+                    )),
+                    complexity: Complexity::Factorial,
+                    speed: 10,
+                },
+                Algorithm {
+                    name: "Bozo sort",
+                    params: &[BUDGET],
+                    new: |params, rng| Box::new(Budget::new(
+                        BozoSort::new(rng),
+                        BUDGETS[params[0].choice()],
+                    )),
+                    complexity: Complexity::Factorial,
+                    speed: DEFAULT_SPEED,
                 },
                 Algorithm {
                     name: "Bubble sort",
-                    new: || Box::new(BubbleSort::new())
+                    params: &[],
+                    new: |_, _| Box::new(BubbleSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Bucket sort",
+                    params: &BucketSort::PARAMS,
+                    new: |params, _| Box::new(BucketSort::new(params[0].int() as usize)),
+                    complexity: Complexity::Linear,
+                    speed: DEFAULT_SPEED,
                 },
                 Algorithm {
                     name: "Cocktail shaker sort",
-                    new: || Box::new(CocktailShakerSort::new())
+                    params: &[],
+                    new: |_, _| Box::new(CocktailShakerSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Comb sort",
+                    params: &CombSort::PARAMS,
+                    new: |params, _| Box::new(CombSort::new(params[0].int() as f64 / 10.0)),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Counting sort",
+                    params: &[],
+                    new: |_, _| Box::new(CountingSort::new()),
+                    complexity: Complexity::Linear,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Cycle sort",
+                    params: &[],
+                    new: |_, _| Box::new(CycleSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Dual-pivot quicksort",
+                    params: &DualPivotQuickSort::PARAMS,
+                    new: |params, _| Box::new(DualPivotQuickSort::new(
+                        PivotSample::ALL[params[0].choice()],
+                        params[1].int() as usize,
+                    )),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Gnome sort",
+                    params: &[],
+                    new: |_, _| Box::new(GnomeSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Heapsort",
+                    params: &[],
+                    new: |_, _| Box::new(HeapSort::new()),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
                 },
                 Algorithm {
                     name: "Insertion sort",
-                    new: || Box::new(InsertionSort::new())
+                    params: &[],
+                    new: |_, _| Box::new(InsertionSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Introsort",
+                    params: &IntroSort::PARAMS,
+                    new: |params, rng| Box::new(IntroSort::new(
+                        PivotRule::ALL[params[0].choice()],
+                        params[1].int() as usize,
+                        params[2].int() as usize,
+                        rng,
+                    )),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Merge sort",
+                    params: &MergeSort::<usize>::PARAMS,
+                    new: |params, _| Box::new(MergeSort::new(Merging::ALL[params[0].choice()])),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Pancake sort",
+                    params: &[],
+                    new: |_, _| Box::new(PancakeSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Pdqsort",
+                    params: &PdqSort::PARAMS,
+                    new: |params, rng| Box::new(PdqSort::new(
+                        PivotRule::ALL[params[0].choice()],
+                        params[1].int() as usize,
+                        rng,
+                    )),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
                 },
                 Algorithm {
                     name: "Quicksort",
-                    new: || Box::new(QuickSort::new())
+                    params: &QuickSort::PARAMS,
+                    new: |params, rng| Box::new(QuickSort::new(
+                        Scheme::ALL[params[0].choice()],
+                        PivotRule::ALL[params[1].choice()],
+                        params[2].int() as usize,
+                        rng,
+                    )),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Quicksort (parallel)",
+                    params: &QuickSort::PARAMS,
+                    new: |params, rng| Box::new(ParallelQuickSort::new(
+                        Scheme::ALL[params[0].choice()],
+                        PivotRule::ALL[params[1].choice()],
+                        params[2].int() as usize,
+                        rng,
+                    )),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Radix sort (LSD)",
+                    params: &RadixSort::PARAMS,
+                    new: |params, _| Box::new(RadixSort::lsd(params[0].int() as usize)),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Radix sort (MSD)",
+                    params: &RadixSort::PARAMS,
+                    new: |params, _| Box::new(RadixSort::msd(params[0].int() as usize)),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
                 },
                 Algorithm {
                     name: "Selection sort",
-                    new: || Box::new(SelectionSort::new())
+                    params: &[],
+                    new: |_, _| Box::new(SelectionSort::new()),
+                    complexity: Complexity::Quadratic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Shell sort",
+                    params: &ShellSort::PARAMS,
+                    new: |params, _| Box::new(ShellSort::new(GapSequence::ALL[params[0].choice()])),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Slowsort",
+                    params: &[BUDGET],
+                    new: |params, _| Box::new(Budget::new(
+                        SlowSort::new(),
+                        BUDGETS[params[0].choice()],
+                    )),
+                    complexity: Complexity::Superpolynomial,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Smoothsort",
+                    params: &[],
+                    new: |_, _| Box::new(SmoothSort::new()),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Sorting network",
+                    params: &NetworkSort::PARAMS,
+                    new: |params, _| Box::new(NetworkSort::new(Network::ALL[params[0].choice()])),
+                    complexity: Complexity::Linear, // in layers, for odd-even transposition
+                    speed: 10, // slow enough to follow each layer
+                },
+                Algorithm {
+                    name: "Stooge sort",
+                    params: &[BUDGET],
+                    new: |params, _| Box::new(Budget::new(
+                        StoogeSort::new(),
+                        BUDGETS[params[0].choice()],
+                    )),
+                    complexity: Complexity::Power(2.71),
+                    speed: DEFAULT_SPEED,
+                },
+                Algorithm {
+                    name: "Timsort",
+                    params: &TimSort::<usize>::PARAMS,
+                    new: |params, _| Box::new(TimSort::new(params[0].int() as usize)),
+                    complexity: Complexity::Linearithmic,
+                    speed: DEFAULT_SPEED,
                 },
         ];
 
-        App {
-            data: core::array::from_fn(|i| i + 1),
+        let params = algorithms.iter()
+            .map(|a| a.params.iter().map(|param| param.default).collect())
+            .collect();
+
+        let overrides = options.overrides();
+        let selected = match overrides.algorithm.as_ref().or(config.algorithm.as_ref()) {
+            Some(name) => algorithms.iter()
+                .position(|a| normalize(a.name) == normalize(name))
+                .ok_or_else(|| format!(
+                    "unknown algorithm \"{name}\", expected one of: {}",
+                    algorithms.iter().map(|a| a.name).collect::<Vec<_>>().join(", "),
+                ))?,
+            None => algorithms.iter().position(|a| a.name == DEFAULT_ALGORITHM)
+                .expect("the default algorithm is in the list"),
+        };
+
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let custom = dataset.is_some();
+        let dataset = dataset.unwrap_or_else(|| {
+            let distribution = overrides.distribution.unwrap_or(config.distribution);
+            Dataset::generate(distribution, overrides.len.unwrap_or(config.len), &mut rng)
+        });
+
+        let mut app = App {
+            data: dataset.data.clone(),
+            swapped: vec![false; dataset.data.len()],
+            compared: vec![false; dataset.data.len()],
+            dataset,
+            custom,
+            rng,
+            config,
+            config_path,
+            overrides,
+            setting: 0,
+            message: None,
+            help: false,
+            paused: false,
+            step_once: false,
+            kiosk: options.kiosk,
+            kiosk_pause: None,
             algorithms,
             current_screen: AppScreen::Menu,
-            selected: 0,
-            sort: None,
-            swapped: vec![],
+            selected,
+            params,
+            param: 0,
+            worker: None,
+            annotations: Annotations::default(),
+            pending: 0.0,
+            stats: Stats::new(),
             n: 0,
+            abandoned: false,
+            expected_steps: 0.0,
+            cursor: None,
+        };
+
+        if app.kiosk && !app.practical() {
+            app.next_algorithm();
+        }
+
+        if options.autostart || options.kiosk {
+            app.start();
+        }
+
+        Ok(app)
+    }
+
+    pub fn start(&mut self) {
+        self.reset_data();
+        let new = self.algorithms[self.selected].new;
+        let params = self.params[self.selected].clone();
+        // each run gets its own generator, drawn from the app's so that `--seed` repeats the whole run
+        let rng = StdRng::seed_from_u64(self.rng.gen());
+        self.worker = Some(Worker::spawn(new, params, rng, self.data.clone()));
+        let complexity = self.algorithms[self.selected].complexity;
+        self.expected_steps = complexity.expected_steps(self.data.len());
+        self.stats = Stats::new();
+        self.pending = 0.0;
+        self.paused = false;
+        self.n = 0;
+        self.abandoned = false;
+        self.kiosk_pause = None;
+        self.current_screen = AppScreen::Sort;
+    }
+
+    fn reset_data(&mut self) {
+        if !self.custom {
+            let len = self.len();
+            self.dataset = Dataset::generate(self.distribution(), len, &mut self.rng);
         }
+        self.data.clone_from(&self.dataset.data);
+        self.swapped = vec![false; self.data.len()];
+        self.compared = vec![false; self.data.len()];
+        self.annotations = Annotations::default();
+        self.cursor = self.cursor.filter(|&i| i < self.data.len());
     }
-}
\ No newline at end of file
+
+    /// Grants the worker however many steps are due after `elapsed` at the current speed, and
+    /// picks up the steps it has run since the last frame.
+    pub fn update(&mut self, elapsed: Duration) {
+        if self.current_screen != AppScreen::Sort {
+            return;
+        }
+
+        let Some(worker) = &self.worker else {
+            if self.paused {
+                // the completion sweep stops too
+            } else if self.n < self.data.len() && !self.abandoned {
+                self.n = (self.n + self.data.len().div_ceil(SWEEP_FRAMES)).min(self.data.len());
+            } else {
+                self.finish();
+            }
+            return;
+        };
+
+        if self.paused {
+            if mem::take(&mut self.step_once) {
+                worker.send(Command::Step);
+            }
+        } else {
+            self.pending += elapsed.as_secs_f64() * self.speed() as f64;
+            let steps = self.pending.floor();
+            self.pending -= steps;
+            if steps > 0.0 {
+                worker.send(Command::Run(steps as u64));
+            }
+        }
+
+        // marks stay up until the next batch arrives, so that they remain visible at low speeds
+        let mut first = true;
+        let mut done = false;
+        while let Some(batch) = worker.poll() {
+            if mem::take(&mut first) {
+                self.swapped.fill(false);
+                self.compared.fill(false);
+            }
+            for (swapped, batch_swapped) in self.swapped.iter_mut().zip(batch.swapped) {
+                *swapped |= batch_swapped;
+            }
+            for (compared, batch_compared) in self.compared.iter_mut().zip(batch.compared) {
+                *compared |= batch_compared;
+            }
+            self.data = batch.data;
+            self.annotations = batch.annotations;
+            self.stats.steps += batch.steps;
+            self.stats.comparisons += batch.comparisons;
+            self.stats.writes += batch.writes;
+            done |= batch.done;
+            self.abandoned |= batch.abandoned;
+        }
+
+        if done {
+            if self.abandoned {
+                let name = self.algorithms[self.selected].name;
+                self.message = Some(format!("{name} gave up after {} steps", self.stats.steps));
+            }
+            self.worker = None;
+            self.annotations = Annotations::default();
+            self.stats.pause();
+        }
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.speed_setting().unwrap_or(self.algorithms[self.selected].speed)
+    }
+
+    /// The speed chosen for every algorithm, if there is one, rather than each one's own default.
+    fn speed_setting(&self) -> Option<u32> {
+        self.overrides.speed.or(self.config.speed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.overrides.len.unwrap_or(self.config.len)
+    }
+
+    pub fn distribution(&self) -> Distribution {
+        self.overrides.distribution.unwrap_or(self.config.distribution)
+    }
+
+    pub fn view(&self) -> View {
+        self.overrides.view.unwrap_or(self.config.view)
+    }
+
+    pub fn theme(&self) -> &'static Theme {
+        self.overrides.theme.unwrap_or(self.config.theme)
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        let Some(worker) = &self.worker else {
+            return; // already done, so there's no running time to stop
+        };
+        if self.paused {
+            worker.send(Command::Pause);
+            self.stats.pause();
+        } else {
+            worker.send(Command::Resume);
+            self.stats.resume();
+        }
+    }
+
+    /// Moves to the next faster or slower of the preset speeds.
+    pub fn change_speed(&mut self, faster: bool) {
+        let speed = self.speed();
+        let next = if faster {
+            SPEEDS.into_iter().find(|&s| s > speed)
+        } else {
+            SPEEDS.into_iter().rev().find(|&s| s < speed)
+        };
+        self.overrides.speed = Some(next.unwrap_or(speed));
+    }
+
+    /// Whether the selected algorithm can be expected to finish in reasonable time at the current
+    /// array length and speed.
+    fn practical(&self) -> bool {
+        let steps = self.algorithms[self.selected].complexity.expected_steps(self.data.len());
+        steps / self.speed() as f64 <= KIOSK_TIME_LIMIT.as_secs_f64()
+    }
+
+    pub fn next_algorithm(&mut self) {
+        for _ in 0..self.algorithms.len() {
+            self.selected = (self.selected + 1) % self.algorithms.len();
+            if self.practical() {
+                return;
+            }
+        }
+    }
+
+    /// Called once the completion sweep is over. Returns to the menu, or in kiosk mode moves on
+    /// to the next algorithm after a pause.
+    pub fn finish(&mut self) {
+        if !self.kiosk {
+            self.current_screen = AppScreen::Menu;
+            return;
+        }
+
+        match self.kiosk_pause {
+            None => self.kiosk_pause = Some(Instant::now() + KIOSK_PAUSE),
+            Some(end) if Instant::now() >= end => {
+                self.next_algorithm();
+                self.start();
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// The value of `setting` in effect, marked if it's only for this run and so won't be saved.
+    pub fn setting_value(&self, setting: Setting) -> String {
+        let value = match setting {
+            Setting::Algorithm => self.default_algorithm().to_string(),
+            Setting::Speed => match self.speed_setting() {
+                Some(speed) => format!("{speed} steps/s"),
+                None => "auto".to_string(),
+            },
+            Setting::Length => self.len().to_string(),
+            Setting::Distribution => self.distribution().name().to_string(),
+            Setting::View => self.view().name().to_string(),
+            Setting::Theme => self.theme().name.to_string(),
+        };
+        let overridden = match setting {
+            Setting::Algorithm => self.overrides.algorithm.is_some(),
+            Setting::Speed => self.overrides.speed.is_some(),
+            Setting::Length => self.overrides.len.is_some(),
+            Setting::Distribution => self.overrides.distribution.is_some(),
+            Setting::View => self.overrides.view.is_some(),
+            Setting::Theme => self.overrides.theme.is_some(),
+        };
+        if overridden {
+            value + " (this run only)"
+        } else {
+            value
+        }
+    }
+
+    fn default_algorithm(&self) -> &'static str {
+        self.overrides.algorithm.as_ref().or(self.config.algorithm.as_ref())
+            .and_then(|name| self.algorithms.iter().find(|a| normalize(a.name) == normalize(name)))
+            .map_or(DEFAULT_ALGORITHM, |a| a.name)
+    }
+
+    /// Changes the selected setting, starting from the value in effect. The change is saved along
+    /// with the config file, so it replaces anything set for this run only.
+    pub fn adjust_setting(&mut self, forward: bool) {
+        match Setting::ALL[self.setting] {
+            Setting::Algorithm => {
+                let names: Vec<_> = self.algorithms.iter().map(|a| a.name).collect();
+                self.config.algorithm = Some(cycle(&names, self.default_algorithm(), forward).to_string());
+                self.overrides.algorithm = None;
+            }
+            Setting::Speed => {
+                self.config.speed = match (self.speed_setting(), forward) {
+                    (None, true) => Some(SPEEDS[0]),
+                    (None, false) => Some(SPEEDS[SPEEDS.len() - 1]),
+                    (Some(speed), true) => SPEEDS.into_iter().find(|&s| s > speed),
+                    (Some(speed), false) => SPEEDS.into_iter().rev().find(|&s| s < speed),
+                };
+                self.overrides.speed = None;
+            }
+            Setting::Length => {
+                self.config.len = if forward {
+                    (self.len() + 10).min(1000)
+                } else {
+                    self.len().saturating_sub(10).max(2)
+                };
+                self.overrides.len = None;
+                self.reset_data();
+            }
+            Setting::Distribution => {
+                self.config.distribution = cycle(&Distribution::ALL, self.distribution(), forward);
+                self.overrides.distribution = None;
+                self.reset_data();
+            }
+            Setting::View => {
+                self.config.view = self.view().next();
+                self.overrides.view = None;
+            }
+            Setting::Theme => {
+                self.config.theme = self.theme().cycle(forward);
+                self.overrides.theme = None;
+            }
+        }
+    }
+
+    /// Opens the options panel for the selected algorithm, if it has any params.
+    pub fn open_options(&mut self) {
+        let algorithm = &self.algorithms[self.selected];
+        if algorithm.params.is_empty() {
+            self.message = Some(format!("{} has no options", algorithm.name));
+        } else {
+            self.param = 0;
+            self.current_screen = AppScreen::Options;
+        }
+    }
+
+    pub fn adjust_param(&mut self, forward: bool) {
+        let param = &self.algorithms[self.selected].params[self.param];
+        let value = &mut self.params[self.selected][self.param];
+        *value = param.adjust(*value, forward);
+    }
+
+    pub fn save_config(&mut self) {
+        self.message = Some(match &self.config_path {
+            Some(path) => match self.config.save(path) {
+                Ok(()) => format!("saved to {}", path.display()),
+                Err(e) => format!("couldn't save to {}: {e}", path.display()),
+            },
+            None => "no config directory found".to_string(),
+        });
+    }
+
+    pub fn move_cursor(&mut self, forward: bool) {
+        let last = self.data.len() - 1;
+        self.cursor = Some(match (self.cursor, forward) {
+            (None, true) => 0,
+            (None, false) => last,
+            (Some(i), true) => if i == last { 0 } else { i + 1 },
+            (Some(i), false) => if i == 0 { last } else { i - 1 },
+        });
+    }
+}
+
+// lets algorithms be named loosely on the command line, e.g. "bubble" or "Bubble-Sort"
+fn normalize(name: &str) -> String {
+    let name: String = name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    name.strip_suffix("sort").unwrap_or(&name).to_string()
+}
+
+fn cycle<T: Copy + PartialEq>(items: &[T], current: T, forward: bool) -> T {
+    let i = items.iter().position(|&item| item == current).unwrap_or(0);
+    if forward {
+        items[(i + 1) % items.len()]
+    } else {
+        items[(i + items.len() - 1) % items.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::*;
+
+    // each choice picks the value at its index, so the labels have to name them in the same order
+    #[test]
+    fn choices_follow_values() {
+        fn check<V: Debug>(param: &Param, values: &[V]) {
+            let Kind::Choice(labels) = param.kind else {
+                panic!("{} isn't a choice", param.name);
+            };
+            assert_eq!(labels.len(), values.len(), "{}", param.name);
+            for (label, value) in labels.iter().zip(values) {
+                let words: String = label.chars().filter(|c| c.is_alphanumeric()).collect();
+                let value = format!("{value:?}");
+                let named = words.to_lowercase().starts_with(&value.to_lowercase());
+                assert!(named, "{label} isn't {value}");
+            }
+        }
+
+        check(&QuickSort::PARAMS[0], &Scheme::ALL);
+        check(&QuickSort::PARAMS[1], &PivotRule::ALL);
+        check(&IntroSort::PARAMS[0], &PivotRule::ALL);
+        check(&PdqSort::PARAMS[0], &PivotRule::ALL);
+        check(&DualPivotQuickSort::PARAMS[0], &PivotSample::ALL);
+        check(&MergeSort::<usize>::PARAMS[0], &Merging::ALL);
+        check(&ShellSort::PARAMS[0], &GapSequence::ALL);
+        check(&NetworkSort::PARAMS[0], &Network::ALL);
+        check(&BUDGET, &BUDGETS);
+    }
+
+    #[test]
+    fn overrides_arent_saved() {
+        let args = "--algo heapsort --n 64 --speed 5000 --theme monochrome";
+        let options = Options::parse(args.split_whitespace().map(String::from)).unwrap();
+        let mut app = App::new(Config::default(), None, &options, None).unwrap();
+        assert_eq!(app.algorithms[app.selected].name, "Heapsort");
+        assert_eq!((app.data.len(), app.speed(), app.theme().name), (64, 5000, "monochrome"));
+        assert_eq!(app.config.to_toml(), Config::default().to_toml());
+
+        // changing a setting on the settings screen replaces the override with a saved value
+        app.setting = Setting::ALL.iter().position(|&s| s == Setting::Length).unwrap();
+        app.adjust_setting(true);
+        assert_eq!((app.len(), app.config.len), (74, 74));
+        assert!(app.overrides.len.is_none());
+        assert_eq!(app.config.speed, None);
+    }
+
+    #[test]
+    fn default_algorithm() {
+        let app = App::new(Config::default(), None, &Options::parse([]).unwrap(), None).unwrap();
+        assert_eq!(app.algorithms[app.selected].name, DEFAULT_ALGORITHM);
+        assert_eq!(app.setting_value(Setting::Algorithm), DEFAULT_ALGORITHM);
+    }
+}
@@ -0,0 +1,169 @@
+// Command-line options. Everything here can also be changed interactively; the flags exist so that
+// the visualizer can be started in a known state, e.g. for scripted demos. Settings given here
+// override the ones in the config file.
+
+use crate::{app::View, config::Overrides, input::Distribution, theme::Theme};
+
+pub const USAGE: &str = "\
+Usage: sorting_algorithm_visualizer [OPTIONS] [FILE]
+
+Sorts the numbers in FILE (one per line, or the first column of a CSV file), or the numbers piped
+in on stdin. Without input, a generated dataset is sorted.
+
+Options:
+  -a, --algo <NAME>          algorithm to select, e.g. \"quicksort\" or \"bubble\"
+  -n, --n <LEN>              length of the generated dataset [default: 100]
+  -d, --distribution <DIST>  random, sorted, reversed, nearly-sorted or few-unique [default: random]
+  -s, --seed <SEED>          seed for generating data and for random choices made while sorting
+      --speed <STEPS>        steps per second
+      --view <VIEW>          bars or dots [default: bars]
+      --theme <THEME>        default, ocean, colorblind or monochrome [default: default]
+      --autostart            start sorting immediately
+      --kiosk                cycle through all algorithms indefinitely
+      --config <PATH>        config file to use instead of the one in the user's config directory
+  -h, --help                 print this message";
+
+pub struct Options {
+    pub algorithm: Option<String>,
+    pub len: Option<usize>,
+    pub distribution: Option<Distribution>,
+    pub seed: Option<u64>,
+    pub speed: Option<u32>,
+    pub view: Option<View>,
+    pub theme: Option<&'static Theme>,
+    pub autostart: bool,
+    pub kiosk: bool,
+    pub config: Option<String>,
+    pub input: Option<String>,
+    pub help: bool,
+}
+
+impl Options {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            algorithm: None,
+            len: None,
+            distribution: None,
+            seed: None,
+            speed: None,
+            view: None,
+            theme: None,
+            autostart: false,
+            kiosk: false,
+            config: None,
+            input: None,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline.clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{flag} requires a value"))
+            };
+
+            match flag.as_str() {
+                "-a" | "--algo" => options.algorithm = Some(value()?),
+                "-n" | "--n" => options.len = Some(parse_positive(&flag, &value()?)?),
+                "-d" | "--distribution" => {
+                    let name = value()?;
+                    options.distribution = Some(Distribution::from_name(&name)
+                        .ok_or_else(|| format!("unknown distribution \"{name}\""))?);
+                }
+                "-s" | "--seed" => {
+                    let seed = value()?;
+                    options.seed = Some(seed.parse()
+                        .map_err(|_| format!("{flag} expects an integer, got \"{seed}\""))?);
+                }
+                "--speed" => options.speed = Some(parse_positive(&flag, &value()?)?),
+                "--view" => {
+                    let name = value()?;
+                    options.view = Some(View::from_name(&name)
+                        .ok_or_else(|| format!("unknown view \"{name}\""))?);
+                }
+                "--theme" => {
+                    let name = value()?;
+                    options.theme = Some(Theme::from_name(&name)
+                        .ok_or_else(|| format!("unknown theme \"{name}\""))?);
+                }
+                "--autostart" => options.autostart = true,
+                "--kiosk" => options.kiosk = true,
+                "--config" => options.config = Some(value()?),
+                "-h" | "--help" => options.help = true,
+                _ if flag.starts_with('-') && flag != "-" => {
+                    return Err(format!("unknown option {flag}"));
+                }
+                _ if options.input.is_none() => options.input = Some(arg),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+
+        if options.len.is_some() && options.input.is_some() {
+            return Err("--n can't be used with an input file".to_string());
+        }
+
+        Ok(options)
+    }
+
+    /// The settings given on the command line, to take precedence over the config file.
+    pub fn overrides(&self) -> Overrides {
+        Overrides {
+            algorithm: self.algorithm.clone(),
+            speed: self.speed,
+            len: self.len,
+            distribution: self.distribution,
+            view: self.view,
+            theme: self.theme,
+        }
+    }
+}
+
+fn parse_positive<N>(flag: &str, value: &str) -> Result<N, String>
+where
+    N: std::str::FromStr + PartialEq + Default,
+{
+    match value.parse::<N>() {
+        Ok(n) if n != N::default() => Ok(n),
+        _ => Err(format!("{flag} expects a positive integer, got \"{value}\"")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        Options::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn flags() {
+        let options = parse("--algo heapsort --n 64 --autostart -d reversed --speed=30").unwrap();
+        assert_eq!(options.algorithm.as_deref(), Some("heapsort"));
+        assert_eq!(options.len, Some(64));
+        assert_eq!(options.distribution, Some(Distribution::Reversed));
+        assert_eq!(options.speed, Some(30));
+        assert!(options.autostart);
+        assert!(options.input.is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse("--n").is_err());
+        assert!(parse("--n 0").is_err());
+        assert!(parse("--speed fast").is_err());
+        assert!(parse("--view pie").is_err());
+        assert!(parse("--theme neon").is_err());
+        assert!(parse("--bogus").is_err());
+        assert!(parse("data.csv --n 5").is_err());
+        assert!(parse("--seed 0").is_ok());
+    }
+}
@@ -0,0 +1,237 @@
+// User configuration, stored in the user's config directory. The file is a small subset of TOML:
+// `key = value` pairs, optionally grouped under `[section]` headers, with `#` comments. Command-line
+// flags take precedence over anything set here, but are kept apart from it so they're never saved.
+
+use std::{
+    env,
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::View,
+    input::Distribution,
+    keys::Bindings,
+    theme::{Theme, THEMES},
+};
+
+pub const SPEEDS: [u32; 14] = [
+    5, 10, 25, 50, 125, 250, 500, 1000, 2500, 10_000, 50_000, 250_000, 1_000_000, 5_000_000,
+];
+
+pub struct Config {
+    pub algorithm: Option<String>,
+    pub speed: Option<u32>, // steps per second; each algorithm has its own default
+    pub len: usize,
+    pub distribution: Distribution,
+    pub view: View,
+    pub theme: &'static Theme,
+    pub keys: Bindings,
+}
+
+/// Settings for this run only, from command-line flags or hotkeys, which take precedence over the
+/// config file but are never saved to it.
+#[derive(Default)]
+pub struct Overrides {
+    pub algorithm: Option<String>,
+    pub speed: Option<u32>,
+    pub len: Option<usize>,
+    pub distribution: Option<Distribution>,
+    pub view: Option<View>,
+    pub theme: Option<&'static Theme>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            algorithm: None,
+            speed: None,
+            len: crate::ARRAY_LEN,
+            distribution: Distribution::Random,
+            view: View::Bars,
+            theme: &THEMES[0],
+            keys: Bindings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/sorting_algorithm_visualizer/config.toml`, falling back to `~/.config`,
+    /// or `%APPDATA%` on Windows.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+        Some(dir.join("sorting_algorithm_visualizer").join("config.toml"))
+    }
+
+    /// Loads the config at `path`. A missing file isn't an error, it just means the defaults.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("couldn't read {}: {e}", path.display())),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section = String::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = format!("{}.", name.trim());
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `key = value`", i + 1));
+            };
+            let key = section.clone() + key.trim();
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+
+            config.set(&key, value).map_err(|e| format!("line {}: {e}", i + 1))?;
+        }
+
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value \"{value}\" for {key}");
+
+        match key {
+            "algorithm" => self.algorithm = Some(value.to_string()),
+            "speed" if value == "auto" => self.speed = None,
+            "speed" => self.speed = Some(value.parse().ok().filter(|&s| s > 0).ok_or_else(invalid)?),
+            "len" => self.len = value.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?,
+            "distribution" => self.distribution = Distribution::from_name(value).ok_or_else(invalid)?,
+            "view" => self.view = View::from_name(value).ok_or_else(invalid)?,
+            "theme" => self.theme = Theme::from_name(value).ok_or_else(invalid)?,
+            _ => match key.strip_prefix("keys.") {
+                Some(action) => self.keys.set(action, value)?,
+                None => return Err(format!("unknown setting \"{key}\"")),
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = String::new();
+        if let Some(algorithm) = &self.algorithm {
+            text += &format!("algorithm = \"{algorithm}\"\n");
+        }
+        match self.speed {
+            Some(speed) => text += &format!("speed = {speed}\n"),
+            None => text += "speed = \"auto\"\n",
+        }
+        text += &format!("len = {}\n", self.len);
+        text += &format!("distribution = \"{}\"\n", self.distribution.name());
+        text += &format!("view = \"{}\"\n", self.view.name());
+        text += &format!("theme = \"{}\"\n", self.theme.name);
+        text += "\n[keys]\n";
+        text += &self.keys.to_toml();
+        text
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
+
+/// `line` without its comment, if it has one. A `#` inside a quoted string isn't a comment.
+fn strip_comment(line: &str) -> &str {
+    let (mut quoted, mut escaped) = (false, false);
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyCode;
+
+    use super::*;
+    use crate::keys::Action;
+
+    #[test]
+    fn round_trip() {
+        let config = Config::parse(
+            "# defaults for the lecture hall\n\
+             algorithm = \"Quicksort\"\n\
+             speed = 50\n\
+             len = 64 # fits the projector\n\
+             distribution = \"reversed\"\n\
+             view = \"dots\"\n\
+             theme = \"monochrome\"\n\
+             \n\
+             [keys]\n\
+             preset = \"vim\"\n\
+             quit = [\"q\"]\n"
+        ).unwrap();
+
+        assert_eq!(config.algorithm.as_deref(), Some("Quicksort"));
+        assert_eq!(config.speed, Some(50));
+        assert_eq!(config.len, 64);
+        assert_eq!(config.distribution, Distribution::Reversed);
+        assert_eq!(config.view, View::Dots);
+        assert_eq!(config.theme.name, "monochrome");
+        assert_eq!(config.keys.keys(Action::Up), [KeyCode::Up, KeyCode::Char('k')]);
+        assert_eq!(config.keys.keys(Action::Quit), [KeyCode::Char('q')]);
+
+        let saved = Config::parse(&config.to_toml()).unwrap();
+        assert_eq!(saved.to_toml(), config.to_toml());
+    }
+
+    #[test]
+    fn punctuation_keys() {
+        let config = Config::parse(
+            "[keys]\n\
+             quit = [\"#\", \"q\"] # the first one isn't a comment\n\
+             pause = [\"=\", \"[\", \"]\"]\n\
+             step = [\",\", \"\\\"\", \"\\\\\"]\n"
+        ).unwrap();
+        assert_eq!(config.keys.keys(Action::Quit), [KeyCode::Char('#'), KeyCode::Char('q')]);
+        let brackets = [KeyCode::Char('='), KeyCode::Char('['), KeyCode::Char(']')];
+        assert_eq!(config.keys.keys(Action::Pause), brackets);
+
+        let saved = Config::parse(&config.to_toml()).unwrap();
+        assert_eq!(saved.keys.keys(Action::Quit), config.keys.keys(Action::Quit));
+        assert_eq!(saved.keys.keys(Action::Pause), config.keys.keys(Action::Pause));
+        assert_eq!(config.keys.keys(Action::Step), [',', '"', '\\'].map(KeyCode::Char));
+        assert_eq!(saved.keys.keys(Action::Step), config.keys.keys(Action::Step));
+        assert_eq!(saved.to_toml(), config.to_toml());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Config::parse("len = 0").is_err());
+        assert!(Config::parse("speed = fast").is_err());
+        assert!(Config::parse("colour = \"red\"").is_err());
+        assert!(Config::parse("len").is_err());
+        assert!(Config::parse("[keys]\nup = \"Hyper\"").is_err());
+        assert!(Config::parse("").is_ok());
+    }
+}
@@ -0,0 +1,203 @@
+// Datasets to be sorted, either generated from one of the built-in distributions or loaded from
+// user input. Input is either one number per line or a CSV file, in which case the first column is
+// used. The numbers themselves can be anything `f64` can represent, so the sorts don't operate on
+// them directly: each value is replaced by its rank among the distinct values, which sorts the
+// same way and can always be mapped back to the original number.
+
+use std::{
+    error::Error,
+    fmt,
+    fs,
+    io::{self, Read},
+};
+
+use rand::Rng;
+
+use crate::sort::shuffle;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Distribution {
+    Random,
+    Sorted,
+    Reversed,
+    NearlySorted,
+    FewUnique,
+}
+
+impl Distribution {
+    pub const ALL: [Distribution; 5] = [
+        Distribution::Random,
+        Distribution::Sorted,
+        Distribution::Reversed,
+        Distribution::NearlySorted,
+        Distribution::FewUnique,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Random => "random",
+            Distribution::Sorted => "sorted",
+            Distribution::Reversed => "reversed",
+            Distribution::NearlySorted => "nearly-sorted",
+            Distribution::FewUnique => "few-unique",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Distribution> {
+        Distribution::ALL.into_iter().find(|d| d.name() == name)
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(String, io::Error),
+    Parse { line: usize, text: String },
+    Empty,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(source, e) => write!(f, "couldn't read {source}: {e}"),
+            InputError::Parse { line, text } => {
+                write!(f, "line {line}: \"{text}\" is not a finite number")
+            }
+            InputError::Empty => write!(f, "input contains no numbers"),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+pub struct Dataset {
+    /// Ranks of the input values in their original order, starting at 1.
+    pub data: Vec<usize>,
+    /// The distinct input values in ascending order; rank `r` corresponds to `values[r - 1]`.
+    pub values: Vec<f64>,
+}
+
+impl Dataset {
+    /// The default dataset, where every value is its own rank.
+    pub fn sequence(len: usize) -> Dataset {
+        Dataset {
+            data: (1..=len).collect(),
+            values: (1..=len).map(|v| v as f64).collect(),
+        }
+    }
+
+    pub fn generate(distribution: Distribution, len: usize, rng: &mut impl Rng) -> Dataset {
+        let mut dataset = Dataset::sequence(len);
+        match distribution {
+            Distribution::Random => shuffle(&mut dataset.data, rng),
+            Distribution::Sorted => (),
+            Distribution::Reversed => dataset.data.reverse(),
+            Distribution::NearlySorted => {
+                for _ in 0..len / 20 + 1 {
+                    let i = rng.gen_range(0..len);
+                    let j = (i + rng.gen_range(1..=3)).min(len - 1);
+                    dataset.data.swap(i, j);
+                }
+            }
+            Distribution::FewUnique => {
+                let unique = len.min(5);
+                dataset.data = (0..len).map(|i| i * unique / len + 1).collect();
+                dataset.values = (1..=unique).map(|v| (v * len / unique) as f64).collect();
+                shuffle(&mut dataset.data, rng);
+            }
+        }
+        dataset
+    }
+
+    /// Reads a dataset from `path`, or from stdin if `path` is `-`.
+    pub fn load(path: &str) -> Result<Dataset, InputError> {
+        let text = if path == "-" {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| InputError::Io("stdin".to_string(), e))?;
+            text
+        } else {
+            fs::read_to_string(path).map_err(|e| InputError::Io(path.to_string(), e))?
+        };
+
+        Dataset::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Dataset, InputError> {
+        let mut numbers = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let field = line.split(',').next().unwrap_or("").trim();
+            if field.is_empty() {
+                continue;
+            }
+            match field.parse::<f64>() {
+                Ok(n) if n.is_finite() => numbers.push(n),
+                // a CSV file is allowed a header row
+                Err(_) if i == 0 && line.contains(',') => (),
+                _ => {
+                    return Err(InputError::Parse { line: i + 1, text: field.to_string() });
+                }
+            }
+        }
+
+        if numbers.is_empty() {
+            return Err(InputError::Empty);
+        }
+
+        let mut values = numbers.clone();
+        values.sort_by(f64::total_cmp);
+        values.dedup();
+
+        let data = numbers
+            .iter()
+            .map(|n| values.partition_point(|v| v < n) + 1)
+            .collect();
+
+        Ok(Dataset { data, values })
+    }
+
+    pub fn value(&self, rank: usize) -> f64 {
+        self.values[rank - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributions() {
+        let mut rng = rand::thread_rng();
+        for distribution in Distribution::ALL {
+            let mut dataset = Dataset::generate(distribution, 50, &mut rng);
+            assert_eq!(dataset.data.len(), 50);
+            dataset.data.sort();
+            assert_eq!(dataset.data[0], 1);
+            assert_eq!(dataset.data[49], dataset.values.len());
+        }
+    }
+
+    #[test]
+    fn ranks() {
+        let dataset = Dataset::parse("3.5\n-2\n\n10\n3.5\n").unwrap();
+        assert_eq!(dataset.data, vec![2, 1, 3, 2]);
+        assert_eq!(dataset.values, vec![-2.0, 3.5, 10.0]);
+    }
+
+    #[test]
+    fn csv_column() {
+        let dataset = Dataset::parse("value,name\n7,a\n5,b\n").unwrap();
+        assert_eq!(dataset.data, vec![2, 1]);
+    }
+
+    #[test]
+    fn malformed() {
+        match Dataset::parse("1\n2\nthree\n") {
+            Err(InputError::Parse { line: 3, .. }) => (),
+            _ => panic!("expected a parse error on line 3"),
+        }
+        assert!(matches!(Dataset::parse("\n\n"), Err(InputError::Empty)));
+        assert!(matches!(Dataset::parse("1\nNaN\n"), Err(InputError::Parse { line: 2, .. })));
+    }
+}
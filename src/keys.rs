@@ -0,0 +1,371 @@
+// Key bindings. Keys are never matched directly; each key press is looked up in the binding table
+// and turned into an `Action`, which is only available on some screens. The footer and the help
+// overlay are generated from the same table, so they always match what the keys actually do.
+
+use std::str::Chars;
+
+use ratatui::crossterm::event::KeyCode;
+
+use crate::app::AppScreen;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Confirm,
+    Quit,
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Step,
+    Faster,
+    Slower,
+    NextView,
+    NextTheme,
+    Settings,
+    Options,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::Confirm,
+        Action::Quit,
+        Action::Back,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Pause,
+        Action::Step,
+        Action::Faster,
+        Action::Slower,
+        Action::NextView,
+        Action::NextTheme,
+        Action::Settings,
+        Action::Options,
+        Action::Help,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Confirm => "confirm",
+            Action::Quit => "quit",
+            Action::Back => "back",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Pause => "pause",
+            Action::Step => "step",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+            Action::NextView => "next_view",
+            Action::NextTheme => "next_theme",
+            Action::Settings => "settings",
+            Action::Options => "options",
+            Action::Help => "help",
+        }
+    }
+
+    /// What the action does on `screen`, or `None` if it isn't available there.
+    pub fn describe(self, screen: AppScreen) -> Option<&'static str> {
+        use AppScreen::*;
+
+        match (self, screen) {
+            (Action::Confirm, Menu) => Some("start sorting"),
+            (Action::Confirm, Settings) => Some("save settings"),
+            (Action::Confirm, Options) => Some("start sorting"),
+            (Action::Quit, Menu) => Some("quit"),
+            (Action::Back, Sort | Settings | Options) => Some("return"),
+            (Action::Up, Menu) => Some("previous algorithm"),
+            (Action::Down, Menu) => Some("next algorithm"),
+            (Action::Up, Settings) => Some("previous setting"),
+            (Action::Down, Settings) => Some("next setting"),
+            (Action::Up, Options) => Some("previous option"),
+            (Action::Down, Options) => Some("next option"),
+            (Action::Left, Menu | Sort) => Some("inspect previous bar"),
+            (Action::Right, Menu | Sort) => Some("inspect next bar"),
+            (Action::Left, Settings) => Some("decrease"),
+            (Action::Right, Settings) => Some("increase"),
+            (Action::Left, Options) => Some("previous choice"),
+            (Action::Right, Options) => Some("next choice"),
+            (Action::Pause, Sort) => Some("pause or resume"),
+            (Action::Step, Sort) => Some("single step while paused"),
+            (Action::Faster, Sort) => Some("speed up"),
+            (Action::Slower, Sort) => Some("slow down"),
+            (Action::NextView, Menu | Sort) => Some("change view"),
+            (Action::NextTheme, Menu | Sort) => Some("change theme"),
+            (Action::Settings, Menu) => Some("settings"),
+            (Action::Options, Menu) => Some("algorithm options"),
+            (Action::Help, _) => Some("show or hide this help"),
+            _ => None,
+        }
+    }
+
+    /// The actions mentioned in the footer of each screen; everything else is in the help overlay.
+    pub fn footer(screen: AppScreen) -> &'static [Action] {
+        match screen {
+            AppScreen::Menu => &[Action::Confirm, Action::Quit, Action::Options, Action::Settings, Action::Help],
+            AppScreen::Sort => &[Action::Back, Action::Pause, Action::Faster, Action::Slower, Action::Help],
+            AppScreen::Settings | AppScreen::Options => {
+                &[Action::Left, Action::Right, Action::Confirm, Action::Back]
+            }
+        }
+    }
+}
+
+pub struct Bindings {
+    keys: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        let key = |c| KeyCode::Char(c);
+        Bindings {
+            keys: vec![
+                (Action::Confirm, vec![KeyCode::Enter]),
+                (Action::Quit, vec![KeyCode::Esc, key('q')]),
+                (Action::Back, vec![KeyCode::Esc]),
+                (Action::Up, vec![KeyCode::Up]),
+                (Action::Down, vec![KeyCode::Down]),
+                (Action::Left, vec![KeyCode::Left]),
+                (Action::Right, vec![KeyCode::Right]),
+                (Action::Pause, vec![key(' ')]),
+                (Action::Step, vec![key('.')]),
+                (Action::Faster, vec![key('+'), key('=')]),
+                (Action::Slower, vec![key('-')]),
+                (Action::NextView, vec![key('v')]),
+                (Action::NextTheme, vec![key('t')]),
+                (Action::Settings, vec![key('s')]),
+                (Action::Options, vec![key('o')]),
+                (Action::Help, vec![key('?')]),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn action(&self, screen: AppScreen, code: KeyCode) -> Option<Action> {
+        self.keys.iter()
+            .find(|(action, keys)| keys.contains(&code) && action.describe(screen).is_some())
+            .map(|&(action, _)| action)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    /// Adds h/j/k/l as alternatives to the arrow keys.
+    pub fn add_vim_keys(&mut self) {
+        for (action, c) in [(Action::Left, 'h'), (Action::Down, 'j'), (Action::Up, 'k'), (Action::Right, 'l')] {
+            self.bind(action, KeyCode::Char(c));
+        }
+    }
+
+    fn bind(&mut self, action: Action, code: KeyCode) {
+        if let Some((_, keys)) = self.keys.iter_mut().find(|(a, _)| *a == action) {
+            if !keys.contains(&code) {
+                keys.push(code);
+            }
+        }
+    }
+
+    /// Sets a config file entry from the `[keys]` section: either `preset = "vim"`, or an action
+    /// name with the list of keys that should trigger it, e.g. `up = ["Up", "k"]`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if name == "preset" {
+            match value {
+                "default" => *self = Bindings::default(),
+                "vim" => self.add_vim_keys(),
+                _ => return Err(format!("unknown key preset \"{value}\"")),
+            }
+            return Ok(());
+        }
+
+        let action = Action::ALL.into_iter()
+            .find(|a| a.name() == name)
+            .ok_or_else(|| format!("unknown action \"{name}\""))?;
+
+        let keys = parse_list(value)?.iter()
+            .map(|key| parse_key(key).ok_or_else(|| format!("unknown key \"{key}\"")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some((_, bound)) = self.keys.iter_mut().find(|(a, _)| *a == action) {
+            *bound = keys;
+        }
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = String::new();
+        for (action, keys) in &self.keys {
+            let keys: Vec<_> = keys.iter().map(|&key| quote(&key_name(key))).collect();
+            text += &format!("{} = [{}]\n", action.name(), keys.join(", "));
+        }
+        text
+    }
+}
+
+/// Reads a TOML array of strings, e.g. `["Up", "k"]`. Strings are quoted, with backslash escapes,
+/// so that any key can be written, even `","` or `"\""`.
+fn parse_list(value: &str) -> Result<Vec<String>, String> {
+    let invalid = || format!("expected a list of keys like [\"Up\", \"k\"], got {value}");
+    let items = value.trim().strip_prefix('[').and_then(|v| v.strip_suffix(']'));
+    let mut chars = items.ok_or_else(invalid)?.chars();
+
+    let mut list = Vec::new();
+    while let Some(c) = chars.find(|c| !c.is_whitespace()) {
+        if c != '"' {
+            return Err(invalid());
+        }
+        let mut item = String::new();
+        loop {
+            match chars.next().ok_or_else(invalid)? {
+                '"' => break,
+                '\\' => item.push(unescape(&mut chars).ok_or_else(invalid)?),
+                c => item.push(c),
+            }
+        }
+        if item.is_empty() {
+            return Err("keys can't be empty strings".to_string());
+        }
+        list.push(item);
+
+        // items are separated by commas, which the last one may have too
+        match chars.find(|c| !c.is_whitespace()) {
+            Some(',') | None => (),
+            Some(_) => return Err(invalid()),
+        }
+    }
+    Ok(list)
+}
+
+/// The character escaped by the backslash just read, as TOML's basic strings escape them.
+fn unescape(chars: &mut Chars) -> Option<char> {
+    match chars.next()? {
+        '"' => Some('"'),
+        '\\' => Some('\\'),
+        'b' => Some('\u{8}'),
+        't' => Some('\t'),
+        'n' => Some('\n'),
+        'f' => Some('\u{c}'),
+        'r' => Some('\r'),
+        'u' => unicode(chars, 4),
+        'U' => unicode(chars, 8),
+        _ => None,
+    }
+}
+
+/// A character written as its code point in `len` hex digits.
+fn unicode(chars: &mut Chars, len: usize) -> Option<char> {
+    let code: String = chars.take(len).collect();
+    if code.len() != len || !code.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+}
+
+/// `key` as a TOML string, escaping whatever would end it early.
+fn quote(key: &str) -> String {
+    format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 12] = [
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Char(' ')),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("Delete", KeyCode::Delete),
+];
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => NAMED_KEYS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|&(_, key)| key),
+    }
+}
+
+pub fn key_name(code: KeyCode) -> String {
+    match NAMED_KEYS.iter().find(|&&(_, key)| key == code) {
+        Some((name, _)) => name.to_string(),
+        None => match code {
+            KeyCode::Char(c) => c.to_string(),
+            _ => format!("{code:?}"),
+        },
+    }
+}
+
+/// A shorter form of `key_name` for the footer and help overlay.
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        code => key_name(code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screens() {
+        let bindings = Bindings::default();
+        assert_eq!(bindings.action(AppScreen::Menu, KeyCode::Esc), Some(Action::Quit));
+        assert_eq!(bindings.action(AppScreen::Sort, KeyCode::Esc), Some(Action::Back));
+        assert_eq!(bindings.action(AppScreen::Settings, KeyCode::Char(' ')), None);
+    }
+
+    #[test]
+    fn remap() {
+        let mut bindings = Bindings::default();
+        bindings.set("preset", "vim").unwrap();
+        bindings.set("pause", "[\"p\", \"Space\"]").unwrap();
+        assert_eq!(bindings.action(AppScreen::Menu, KeyCode::Char('j')), Some(Action::Down));
+        assert_eq!(bindings.action(AppScreen::Sort, KeyCode::Char('p')), Some(Action::Pause));
+        assert!(bindings.set("jump", "x").is_err());
+        assert!(bindings.set("up", "Hyper").is_err());
+    }
+
+    #[test]
+    fn quoted_keys() {
+        let mut bindings = Bindings::default();
+        bindings.set("quit", r#"[",", "\"", "\\", "A",]"#).unwrap();
+        let keys = [',', '"', '\\', 'A'].map(KeyCode::Char);
+        assert_eq!(bindings.keys(Action::Quit), keys);
+        bindings.set("quit", "[]").unwrap();
+        assert_eq!(bindings.keys(Action::Quit), []);
+
+        // anything that would have left the action unbound by mistake is an error
+        for value in [r#"[""]"#, r#"["q" "w"]"#, r#"["q",,]"#, r#"["\x"]"#, r#"["q"#, "[q]", "q"] {
+            assert!(bindings.set("quit", value).is_err(), "{value} parsed");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bindings = Bindings::default();
+        bindings.add_vim_keys();
+
+        let mut saved = Bindings::default();
+        for line in bindings.to_toml().lines() {
+            let (name, value) = line.split_once(" = ").unwrap();
+            saved.set(name, value).unwrap();
+        }
+        assert_eq!(saved.to_toml(), bindings.to_toml());
+    }
+}
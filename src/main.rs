@@ -1,24 +1,49 @@
-use std::{error::Error, io, time::Duration};
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
 
-use app::{App, AppScreen};
+use app::{App, AppScreen, Setting, FPS};
+use cli::Options;
+use config::Config;
+use input::{Dataset, InputError};
+use keys::Action;
 use ratatui::{
     crossterm::{
-        event::{self, Event, KeyCode},
+        event::{self, Event, KeyEvent},
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
     },
     prelude::{Backend, CrosstermBackend},
     Terminal
 };
-use sort::{shuffle, SortResult};
 
 mod app;
+mod cli;
+mod config;
+mod input;
+mod keys;
 mod sort;
+mod theme;
 mod ui;
+mod worker;
 
 pub const ARRAY_LEN: usize = 100;
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // everything that can fail is set up before entering raw mode, so that errors are printed to
+    // a normal terminal
+    let mut app = match setup() {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -26,7 +51,6 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
     run_app(&mut terminal, &mut app)?;
 
     disable_raw_mode()?;
@@ -36,73 +60,141 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn setup() -> Result<App, Box<dyn Error>> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    if options.help {
+        println!("{}", cli::USAGE);
+        process::exit(0);
+    }
+
+    let config_path = options.config.as_ref().map(PathBuf::from).or_else(Config::default_path);
+    let config = match &config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
+    let dataset = match &options.input {
+        Some(path) => Some(Dataset::load(path)?),
+        // stdin is only read implicitly when something is piped in; an empty stdin (e.g. when
+        // started by a service manager) just means there's no input
+        None if !io::stdin().is_terminal() => match Dataset::load("-") {
+            Err(InputError::Empty) => None,
+            result => Some(result?),
+        },
+        None => None,
+    };
+
+    Ok(App::new(config, config_path, &options, dataset)?)
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), Box<dyn Error>> {
+    // the screen is redrawn at a fixed rate, and however many steps are due at the current speed
+    // are run in between
+    let frame = Duration::from_secs(1) / FPS;
+    let mut last_update = Instant::now();
+
     loop {
+        let frame_start = Instant::now();
+        app.update(frame_start - last_update);
+        last_update = frame_start;
+
         terminal.draw(|f| ui::ui(f, app))?;
 
-        if app.current_screen == AppScreen::Sort {
-            if let Some(ref mut sort) = app.sort {
-                match sort.step(&mut app.data) {
-                    SortResult::Done => {
-                        app.sort.take();
-                        app.swapped.clear();
-                    }
-                    SortResult::Swap(vec) => app.swapped = vec,
-                    SortResult::Ok => app.swapped.clear(),
-                }  
-            } else if app.n < app.data.len() {
-                app.n += 1;
-            } else {
-                app.current_screen = AppScreen::Menu;
+        // handle input until it's time for the next frame
+        loop {
+            let timeout = (frame_start + frame).saturating_duration_since(Instant::now());
+            if !event::poll(timeout)? {
+                break;
             }
-        }
-        
-        let duration = if app.current_screen == AppScreen::Sort
-            && app.algorithms[app.selected].name == "Bogosort"
-        {
-            Duration::from_millis(100)
-        } else {
-            Duration::from_millis(8)
-        };
-
-        if event::poll(duration)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == event::KeyEventKind::Release { continue; }
-                match app.current_screen {
-                    AppScreen::Menu => match key.code {
-                        KeyCode::Enter => {
-                            app.sort = Some((app.algorithms[app.selected].new)());
-                            shuffle(&mut app.data);
-                            app.n = 0;
-                            app.current_screen = AppScreen::Sort;
-                        }
-                        KeyCode::Esc => {
-                            return Ok(());
-                        }
-                        KeyCode::Up => {
-                            if app.selected == 0 {
-                                app.selected = app.algorithms.len() - 1;
-                            } else {
-                                app.selected -= 1;
-                            }
-                        }
-                        KeyCode::Down => {
-                            if app.selected == app.algorithms.len() - 1 {
-                                app.selected = 0;
-                            } else {
-                                app.selected += 1;
-                            }
-                        }
-                        _ => ()
-                    }
-                    AppScreen::Sort => match key.code {
-                        KeyCode::Esc => {
-                            app.current_screen = AppScreen::Menu;
-                        }
-                        _ => ()
-                    }
+                if !handle_key(app, key) {
+                    return Ok(());
                 }
             }
         }
     }
 }
+
+/// Returns false if the app should quit.
+fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    if key.kind == event::KeyEventKind::Release { return true; }
+
+    let Some(action) = app.config.keys.action(app.current_screen, key.code) else {
+        return true;
+    };
+
+    // while the help overlay is open, keys only close it
+    if app.help {
+        if matches!(action, Action::Help | Action::Back | Action::Quit) {
+            app.help = false;
+        }
+        return true;
+    }
+
+    // messages on the menu only last until the next key
+    if app.current_screen == AppScreen::Menu {
+        app.message = None;
+    }
+
+    match (app.current_screen, action) {
+        (_, Action::Help) => app.help = true,
+        (_, Action::NextView) => app.overrides.view = Some(app.view().next()),
+        (_, Action::NextTheme) => app.overrides.theme = Some(app.theme().cycle(true)),
+        (AppScreen::Menu, Action::Confirm) => app.start(),
+        (AppScreen::Menu, Action::Quit) => return false,
+        (AppScreen::Menu, Action::Up) => {
+            if app.selected == 0 {
+                app.selected = app.algorithms.len() - 1;
+            } else {
+                app.selected -= 1;
+            }
+        }
+        (AppScreen::Menu, Action::Down) => {
+            if app.selected == app.algorithms.len() - 1 {
+                app.selected = 0;
+            } else {
+                app.selected += 1;
+            }
+        }
+        (AppScreen::Menu, Action::Settings) => app.current_screen = AppScreen::Settings,
+        (AppScreen::Menu, Action::Options) => app.open_options(),
+        (AppScreen::Menu | AppScreen::Sort, Action::Left) => app.move_cursor(false),
+        (AppScreen::Menu | AppScreen::Sort, Action::Right) => app.move_cursor(true),
+        (AppScreen::Sort, Action::Back) => {
+            app.kiosk = false;
+            app.worker = None;
+            app.current_screen = AppScreen::Menu;
+        }
+        (AppScreen::Sort, Action::Pause) => app.toggle_pause(),
+        (AppScreen::Sort, Action::Step) => app.step_once = true,
+        (AppScreen::Sort, Action::Faster) => app.change_speed(true),
+        (AppScreen::Sort, Action::Slower) => app.change_speed(false),
+        (AppScreen::Settings, Action::Back) => {
+            app.message = None;
+            app.current_screen = AppScreen::Menu;
+        }
+        (AppScreen::Settings, Action::Confirm) => app.save_config(),
+        (AppScreen::Settings, Action::Up) => {
+            app.setting = app.setting.checked_sub(1).unwrap_or(Setting::ALL.len() - 1);
+        }
+        (AppScreen::Settings, Action::Down) => {
+            app.setting = (app.setting + 1) % Setting::ALL.len();
+        }
+        (AppScreen::Settings, Action::Left) => app.adjust_setting(false),
+        (AppScreen::Settings, Action::Right) => app.adjust_setting(true),
+        (AppScreen::Options, Action::Back) => app.current_screen = AppScreen::Menu,
+        (AppScreen::Options, Action::Confirm) => app.start(),
+        (AppScreen::Options, Action::Up) => {
+            let params = app.algorithms[app.selected].params.len();
+            app.param = app.param.checked_sub(1).unwrap_or(params - 1);
+        }
+        (AppScreen::Options, Action::Down) => {
+            app.param = (app.param + 1) % app.algorithms[app.selected].params.len();
+        }
+        (AppScreen::Options, Action::Left) => app.adjust_param(false),
+        (AppScreen::Options, Action::Right) => app.adjust_param(true),
+        _ => ()
+    }
+
+    true
+}
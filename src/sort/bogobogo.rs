@@ -0,0 +1,47 @@
+use rand::rngs::StdRng;
+
+use super::*;
+
+/// Bogobogosort, which sorts the first n - 1 elements with bogobogosort and then checks whether
+/// the last one is the largest. If it isn't, it shuffles all n and starts again, right from
+/// sorting the first two. This is the in-place version most implementations use, which is
+/// expected to take about (e - 1) n! comparisons.
+pub struct BogoBogoSort {
+    rng: StdRng,
+    k: usize, // the first `k - 1` elements are sorted, and element `k - 1` is next to check
+    shuffles: u64,
+}
+
+impl BogoBogoSort {
+    pub fn new(rng: StdRng) -> BogoBogoSort {
+        BogoBogoSort {
+            rng,
+            k: 2,
+            shuffles: 0,
+        }
+    }
+}
+
+impl<T> Sort<T> for BogoBogoSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if self.k > a.len() {
+            return SortResult::Done;
+        }
+
+        events.compare(self.k - 2, self.k - 1);
+        if a[self.k - 2] <= a[self.k - 1] {
+            self.k += 1;
+        } else {
+            shuffle(&mut a[..self.k], &mut self.rng);
+            self.shuffles += 1;
+            self.k = 2;
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        annotations.ranges.push(0..self.k);
+        annotations.stats.push(("sorted prefix", (self.k - 1).to_string()));
+        annotations.stats.push(("shuffles", self.shuffles.to_string()));
+    }
+}
@@ -1,16 +1,22 @@
+use rand::rngs::StdRng;
+
 use super::*;
 
-pub struct BogoSort {}
+pub struct BogoSort {
+    rng: StdRng,
+}
 
 impl BogoSort {
-    pub fn new() -> BogoSort {
-        BogoSort {}
+    pub fn new(rng: StdRng) -> BogoSort {
+        BogoSort {
+            rng,
+        }
     }
 }
 
 impl<T> Sort<T> for BogoSort where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
-        shuffle(a);
+    fn step(&mut self, a: &mut [T], _: &mut dyn Events) -> SortResult {
+        shuffle(a, &mut self.rng);
 
         if is_sorted(a) {
             SortResult::Done
@@ -0,0 +1,34 @@
+use rand::rngs::StdRng;
+
+use super::*;
+
+/// Bogosort's slightly less hopeless cousin, which swaps two random elements at a time rather
+/// than shuffling the whole array. It's still expected to take about n! swaps.
+pub struct BozoSort {
+    rng: StdRng,
+}
+
+impl BozoSort {
+    pub fn new(rng: StdRng) -> BozoSort {
+        BozoSort {
+            rng,
+        }
+    }
+}
+
+impl<T> Sort<T> for BozoSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if a.len() >= 2 {
+            let i = self.rng.gen_range(0..a.len());
+            let j = (i + self.rng.gen_range(1..a.len())) % a.len();
+            swap(a, i, j);
+            events.swap(i, j);
+        }
+
+        if is_sorted(a) {
+            SortResult::Done
+        } else {
+            SortResult::Ok
+        }
+    }
+}
@@ -23,20 +23,14 @@ impl BubbleSort {
 }
 
 impl<T> Sort<T> for BubbleSort where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
         let i = self.i;
         let j = self.i - 1;
 
         if i < a.len() - self.n {
-            if a[j] > a[i] {
-                swap(a, i, j);
-                self.swapped = true;
-                self.i += 1;
-                SortResult::Swap(vec![i, j])
-            } else {
-                self.i += 1;
-                SortResult::Ok
-            }
+            self.swapped |= exchange(a, j, i, events);
+            self.i += 1;
+            SortResult::Ok
         } else if !self.swapped {
             SortResult::Done
         } else {
@@ -0,0 +1,86 @@
+use super::*;
+
+/// Distributes the elements into buckets covering equal parts of the range of values, and writes
+/// the buckets back in order. Each element is then already in its own bucket's part of the array, so
+/// a final insertion sort only ever moves elements within a bucket.
+pub struct BucketSort {
+    buckets: Vec<Vec<usize>>,
+    min: usize,
+    span: usize, // max - min + 1
+    phase: Phase,
+}
+
+enum Phase {
+    Start,
+    Scatter { i: usize }, // next element to drop into its bucket
+    Gather { bucket: usize, j: usize, k: usize }, // writing element `j` of `bucket` back to `k`
+    Insertion(InsertionSort),
+}
+
+impl BucketSort {
+    pub const PARAMS: [Param; 1] = [
+        Param {
+            name: "Buckets",
+            kind: Kind::Int { min: 2, max: 64 },
+            default: Value::Int(10),
+        },
+    ];
+
+    pub fn new(buckets: usize) -> BucketSort {
+        BucketSort {
+            buckets: (0..buckets).map(|_| Vec::new()).collect(),
+            min: 0,
+            span: 1,
+            phase: Phase::Start,
+        }
+    }
+}
+
+impl Sort<usize> for BucketSort {
+    fn step(&mut self, a: &mut [usize], events: &mut dyn Events) -> SortResult {
+        loop {
+            match &mut self.phase {
+                Phase::Start => {
+                    self.min = a.iter().copied().min().unwrap_or(0);
+                    self.span = a.iter().copied().max().unwrap_or(0) - self.min + 1;
+                    for bucket in &mut self.buckets {
+                        bucket.reserve(a.len());
+                    }
+                    self.phase = Phase::Scatter { i: 0 };
+                }
+                Phase::Scatter { i } if *i < a.len() => {
+                    events.read(*i);
+                    let bucket = (a[*i] - self.min) * self.buckets.len() / self.span;
+                    self.buckets[bucket].push(a[*i]);
+                    *i += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Scatter { .. } => self.phase = Phase::Gather { bucket: 0, j: 0, k: 0 },
+                // skip past buckets that are empty or have been written back
+                Phase::Gather { bucket, j, .. }
+                    if *bucket < self.buckets.len() && *j == self.buckets[*bucket].len() =>
+                {
+                    *bucket += 1;
+                    *j = 0;
+                }
+                Phase::Gather { bucket, j, k } if *bucket < self.buckets.len() => {
+                    a[*k] = self.buckets[*bucket][*j];
+                    events.write(*k);
+                    *j += 1;
+                    *k += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Gather { .. } => self.phase = Phase::Insertion(InsertionSort::new()),
+                Phase::Insertion(sort) => return sort.step(a, events),
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        match self.phase {
+            Phase::Start | Phase::Insertion(_) => (),
+            Phase::Scatter { .. } => annotations.buckets.clone_from(&self.buckets),
+            Phase::Gather { bucket, j, .. } => annotations.buckets = unwritten(&self.buckets, bucket, j),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+// the choices of `BUDGET`, in steps
+pub const BUDGETS: [u64; 5] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+pub const BUDGET: Param = Param {
+    name: "Give up after",
+    kind: Kind::Choice(&[
+        "1,000 steps",
+        "10,000 steps",
+        "100,000 steps",
+        "1,000,000 steps",
+        "10,000,000 steps",
+    ]),
+    default: Value::Choice(2),
+};
+
+/// Runs a sort that could take longer than anyone would wait, abandoning it once it has taken
+/// `limit` steps.
+pub struct Budget<S> {
+    sort: S,
+    steps: u64,
+    limit: u64,
+}
+
+impl<S> Budget<S> {
+    pub fn new(sort: S, limit: u64) -> Budget<S> {
+        Budget {
+            sort,
+            steps: 0,
+            limit,
+        }
+    }
+}
+
+impl<T, S> Sort<T> for Budget<S> where T: PartialOrd + Clone, S: Sort<T> {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        self.steps += 1;
+        match self.sort.step(a, events) {
+            SortResult::Ok if self.steps >= self.limit => SortResult::Abandoned,
+            result => result,
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        self.sort.annotate(annotations);
+        annotations.budget = Some((self.steps, self.limit));
+    }
+}
@@ -21,30 +21,16 @@ impl CocktailShakerSort {
         }
     }
 
-    fn forward_pass<T>(&mut self, a: &mut [T]) -> SortResult where T: PartialOrd + Clone {
-        let i = self.i;
-        if a[i] > a[i + 1] {
-            swap(a, i, i + 1);
-            self.swapped = true;
-            self.i += 1;
-            SortResult::Swap(vec![i, i + 1])
-        } else {
-            self.i += 1;
-            SortResult::Ok
-        }
+    fn forward_pass<T>(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult where T: PartialOrd + Clone {
+        self.swapped |= exchange(a, self.i, self.i + 1, events);
+        self.i += 1;
+        SortResult::Ok
     }
 
-    fn backward_pass<T>(&mut self, a: &mut [T]) -> SortResult where T: PartialOrd + Clone {
-        let i = self.i;
-        if a[i] < a[i - 1] {
-            swap(a, i, i - 1);
-            self.swapped = true;
-            self.i -= 1;
-            SortResult::Swap(vec![i, i - 1])
-        } else {
-            self.i -= 1;
-            SortResult::Ok
-        }
+    fn backward_pass<T>(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult where T: PartialOrd + Clone {
+        self.swapped |= exchange(a, self.i - 1, self.i, events);
+        self.i -= 1;
+        SortResult::Ok
     }
 
     fn switch_direction(&mut self) {
@@ -63,14 +49,14 @@ impl CocktailShakerSort {
 }
 
 impl<T> Sort<T> for CocktailShakerSort where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
         let i = self.i;
         match self.direction {
             ShakerDirection::Forward if i < a.len() - (1 + self.end_pad) => {
-                self.forward_pass(a)
+                self.forward_pass(a, events)
             }
             ShakerDirection::Backward if i > self.start_pad => {
-                self.backward_pass(a)
+                self.backward_pass(a, events)
             }
             _ if !self.swapped => SortResult::Done, // pass completed without swapping anything
             _ => { // next pass
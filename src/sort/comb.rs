@@ -0,0 +1,64 @@
+use super::*;
+
+/// Comb sort, which is bubble sort with a gap: each pass compares elements `gap` apart, and the
+/// gap shrinks by a constant factor between passes until it's 1. Small elements near the end,
+/// the turtles that bubble sort can only move one place per pass, are carried most of the way
+/// home by the early passes.
+pub struct CombSort {
+    shrink: f64,
+    gap: usize,
+    i: usize,
+    swapped: bool,
+    started: bool,
+}
+
+impl CombSort {
+    pub const PARAMS: [Param; 1] = [Param {
+        name: "Shrink factor",
+        kind: Kind::Tenths { min: 11, max: 30 },
+        default: Value::Int(13),
+    }];
+
+    pub fn new(shrink: f64) -> CombSort {
+        CombSort {
+            shrink,
+            gap: 1,
+            i: 0,
+            swapped: false,
+            started: false,
+        }
+    }
+
+    fn next_gap(&self, gap: usize) -> usize {
+        ((gap as f64 / self.shrink) as usize).max(1)
+    }
+}
+
+impl<T> Sort<T> for CombSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.gap = self.next_gap(a.len());
+            self.started = true;
+        }
+
+        if self.i + self.gap < a.len() {
+            self.swapped |= exchange(a, self.i, self.i + self.gap, events);
+            self.i += 1;
+            SortResult::Ok
+        } else if self.gap == 1 && !self.swapped {
+            // once the gap is down to 1, it's a bubble sort, which stops after a pass without swaps
+            SortResult::Done
+        } else {
+            self.gap = self.next_gap(self.gap);
+            self.i = 0;
+            self.swapped = false;
+            SortResult::Ok
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if self.started {
+            annotations.stats.push(("gap", self.gap.to_string()));
+        }
+    }
+}
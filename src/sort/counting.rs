@@ -0,0 +1,69 @@
+use super::*;
+
+/// Counts how many times each value occurs, then writes the values back in order as many times as
+/// they were counted. Only works on integers, but never compares two elements.
+pub struct CountingSort {
+    min: usize,
+    counts: Vec<usize>, // indexed by value - min
+    phase: Phase,
+}
+
+enum Phase {
+    Start,
+    Count { i: usize }, // next element to count
+    Write { value: usize, k: usize }, // writing `value` to `k`, if it has any count left
+}
+
+impl CountingSort {
+    pub fn new() -> CountingSort {
+        CountingSort {
+            min: 0,
+            counts: Vec::new(),
+            phase: Phase::Start,
+        }
+    }
+}
+
+impl Sort<usize> for CountingSort {
+    fn step(&mut self, a: &mut [usize], events: &mut dyn Events) -> SortResult {
+        loop {
+            match &mut self.phase {
+                Phase::Start => {
+                    self.min = a.iter().copied().min().unwrap_or(0);
+                    let max = a.iter().copied().max().unwrap_or(0);
+                    self.counts = vec![0; max - self.min + 1];
+                    self.phase = Phase::Count { i: 0 };
+                }
+                Phase::Count { i } if *i < a.len() => {
+                    events.read(*i);
+                    self.counts[a[*i] - self.min] += 1;
+                    *i += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Count { .. } => self.phase = Phase::Write { value: 0, k: 0 },
+                Phase::Write { value, k } if *value < self.counts.len() => {
+                    if self.counts[*value] == 0 {
+                        *value += 1;
+                        continue;
+                    }
+                    self.counts[*value] -= 1;
+                    a[*k] = *value + self.min;
+                    events.write(*k);
+                    *k += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Write { .. } => return SortResult::Done,
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let phase = match self.phase {
+            Phase::Start => return,
+            Phase::Count { .. } => "counting",
+            Phase::Write { .. } => "writing back",
+        };
+        annotations.histogram.clone_from(&self.counts);
+        annotations.stats.push(("phase", phase.to_string()));
+    }
+}
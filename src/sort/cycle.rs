@@ -0,0 +1,102 @@
+use std::mem;
+
+use super::*;
+
+enum State {
+    Take, // picking up the element at `start`
+    Count { i: usize }, // counting the elements smaller than the one held, up to `i`
+    Skip, // moving `pos` past elements equal to the one held, then putting it down there
+}
+
+/// Cycle sort, which writes each element straight to its final position, found by counting the
+/// elements smaller than it. The element that was there is picked up and placed the same way, and
+/// so on around the cycle until one lands where the first was taken from. Elements already in
+/// place are never written, so it makes the fewest writes possible, at the cost of a quadratic
+/// number of comparisons.
+pub struct CycleSort<T> {
+    start: usize, // where the current cycle began; everything before is sorted
+    held: Option<T>,
+    pos: usize, // where the held element is going
+    state: State,
+    placed: bool, // whether anything has been written in the current cycle
+    cycles: usize, // that needed any writes
+    len: usize,
+}
+
+impl<T> CycleSort<T> {
+    pub fn new() -> CycleSort<T> {
+        CycleSort {
+            start: 0,
+            held: None,
+            pos: 0,
+            state: State::Take,
+            placed: false,
+            cycles: 0,
+            len: 0,
+        }
+    }
+
+    fn next_cycle(&mut self) {
+        self.start += 1;
+        self.held = None;
+        self.placed = false;
+        self.state = State::Take;
+    }
+}
+
+impl<T> Sort<T> for CycleSort<T> where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        self.len = a.len();
+        match self.state {
+            // the last element is in place once all the others are
+            State::Take if self.start + 1 >= a.len() => return SortResult::Done,
+            State::Take => {
+                self.held = Some(a[self.start].clone());
+                events.read(self.start);
+                self.pos = self.start;
+                self.state = State::Count { i: self.start + 1 };
+            }
+            State::Count { i } if i < a.len() => {
+                events.compare_held(i);
+                if self.held.as_ref().is_some_and(|held| a[i] < *held) {
+                    self.pos += 1;
+                }
+                self.state = State::Count { i: i + 1 };
+            }
+            State::Count { .. } if !self.placed && self.pos == self.start => self.next_cycle(),
+            State::Count { .. } => self.state = State::Skip,
+            State::Skip => {
+                let Some(held) = &mut self.held else {
+                    unreachable!("nothing is held while skipping");
+                };
+                events.compare_held(self.pos);
+                if a[self.pos] == *held {
+                    self.pos += 1;
+                    return SortResult::Ok;
+                }
+
+                mem::swap(held, &mut a[self.pos]);
+                events.write(self.pos);
+                if !mem::replace(&mut self.placed, true) {
+                    self.cycles += 1;
+                }
+                if self.pos == self.start {
+                    self.next_cycle();
+                } else {
+                    // the element picked up from `pos` goes around again
+                    self.pos = self.start;
+                    self.state = State::Count { i: self.start + 1 };
+                }
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if self.held.is_some() {
+            annotations.pivots.push(self.pos);
+        }
+        annotations.ranges.push(self.start..self.len);
+        annotations.stats.push(("cycles", self.cycles.to_string()));
+    }
+}
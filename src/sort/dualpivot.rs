@@ -0,0 +1,240 @@
+use std::cmp::Reverse;
+
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PivotSample {
+    Ends, // the first and last elements, as in Yaroslavskiy's original
+    Thirds, // the elements a third of the way in from each end, closer to what Java does
+}
+
+impl PivotSample {
+    pub const ALL: [PivotSample; 2] = [PivotSample::Ends, PivotSample::Thirds];
+}
+
+enum State {
+    Choosing, // the first step moves the pivots to the ends, the smaller one first
+    Scan { l: usize, k: usize, g: usize }, // classifying `k`
+    Pull { l: usize, k: usize, g: usize }, // moving `g` left past elements above the right pivot
+    Placed { l: usize, k: usize, g: usize }, // `k` came from `g`, and may be below the left pivot
+    Insertion { i: usize, j: usize },
+}
+
+/// A partition of one range around two pivots, into elements less than the left pivot, between
+/// the two, and greater than the right one. Elements from `l` up to `k` are between the pivots,
+/// and those after `g` are greater.
+struct Partition {
+    range: Range<usize>,
+    state: State,
+}
+
+impl Partition {
+    fn new(range: Range<usize>, cutoff: usize) -> Partition {
+        let state = if range.len() <= cutoff {
+            State::Insertion { i: range.start + 1, j: range.start + 1 }
+        } else {
+            State::Choosing
+        };
+        Partition { range, state }
+    }
+
+    /// Returns the three ranges left between the pivots once the partition is finished, or empty
+    /// ones after an insertion sort.
+    fn step<T>(
+        &mut self,
+        a: &mut [T],
+        sample: PivotSample,
+        events: &mut dyn Events,
+    ) -> Option<[Range<usize>; 3]> where T: PartialOrd + Clone {
+        let (left, right) = (self.range.start, self.range.end - 1);
+
+        match &mut self.state {
+            State::Choosing => {
+                if sample == PivotSample::Thirds {
+                    let third = self.range.len() / 3;
+                    for (end, pivot) in [(left, left + third), (right, right - third)] {
+                        if end != pivot {
+                            swap(a, end, pivot);
+                            events.swap(end, pivot);
+                        }
+                    }
+                }
+                events.compare(left, right);
+                if a[left] > a[right] {
+                    swap(a, left, right);
+                    events.swap(left, right);
+                }
+                self.state = State::Scan { l: left + 1, k: left + 1, g: right - 1 };
+                None
+            }
+            &mut State::Scan { l, k, g } if k <= g => {
+                events.compare(k, left);
+                if a[k] < a[left] {
+                    if k != l {
+                        swap(a, k, l);
+                        events.swap(k, l);
+                    }
+                    self.state = State::Scan { l: l + 1, k: k + 1, g };
+                    return None;
+                }
+                events.compare(k, right);
+                self.state = if a[k] < a[right] {
+                    State::Scan { l, k: k + 1, g }
+                } else {
+                    State::Pull { l, k, g }
+                };
+                None
+            }
+            // everything has been classified, so the pivots go between the regions
+            &mut State::Scan { l, g, .. } => {
+                let (l, g) = (l - 1, g + 1);
+                for (pivot, home) in [(left, l), (right, g)] {
+                    if pivot != home {
+                        swap(a, pivot, home);
+                        events.swap(pivot, home);
+                    }
+                }
+                Some([left..l, l + 1..g, g + 1..right + 1])
+            }
+            &mut State::Pull { l, k, g } => {
+                if k < g {
+                    events.compare(g, right);
+                    if a[g] > a[right] {
+                        self.state = State::Pull { l, k, g: g - 1 };
+                        return None;
+                    }
+                }
+                if k != g {
+                    swap(a, k, g);
+                    events.swap(k, g);
+                }
+                self.state = State::Placed { l, k, g: g - 1 };
+                None
+            }
+            &mut State::Placed { l, k, g } => {
+                events.compare(k, left);
+                let l = if a[k] < a[left] {
+                    if k != l {
+                        swap(a, k, l);
+                        events.swap(k, l);
+                    }
+                    l + 1
+                } else {
+                    l
+                };
+                self.state = State::Scan { l, k: k + 1, g };
+                None
+            }
+            State::Insertion { i, j } => {
+                if *i > right {
+                    return Some([left..left, left..left, left..left]);
+                }
+                if *j > left {
+                    events.compare(*j - 1, *j);
+                }
+                if *j > left && a[*j - 1] > a[*j] {
+                    swap(a, *j, *j - 1);
+                    events.swap(*j, *j - 1);
+                    *j -= 1;
+                } else {
+                    *i += 1;
+                    *j = *i;
+                }
+                None
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let (left, right) = (self.range.start, self.range.end - 1);
+        let (l, k, g) = match self.state {
+            State::Scan { l, k, g } | State::Pull { l, k, g } | State::Placed { l, k, g } => {
+                (l, k, g)
+            }
+            State::Choosing | State::Insertion { .. } => return,
+        };
+        annotations.pivots.extend([left, right]);
+        annotations.regions.extend([
+            (left + 1..l, Region::Less),
+            (l..k.min(g + 1), Region::Between), // `k` can end up past `g` as the scans meet
+            (g + 1..right, Region::Greater),
+        ]);
+    }
+}
+
+/// Vladimir Yaroslavskiy's dual-pivot quicksort, which Java uses to sort arrays of primitives.
+/// Each partition splits its range into three around two pivots, in a single pass that scans
+/// inwards from both ends.
+pub struct DualPivotQuickSort {
+    sample: PivotSample,
+    cutoff: usize, // ranges this short are insertion sorted instead
+    stack: Vec<Range<usize>>,
+    partition: Option<Partition>,
+    started: bool,
+}
+
+impl DualPivotQuickSort {
+    pub const PARAMS: [Param; 2] = [
+        Param {
+            name: "Pivots",
+            kind: Kind::Choice(&["ends", "thirds"]),
+            default: Value::Choice(1),
+        },
+        Param {
+            name: "Insertion cutoff",
+            kind: Kind::Int { min: 0, max: 32 },
+            default: Value::Int(0),
+        },
+    ];
+
+    pub fn new(sample: PivotSample, cutoff: usize) -> DualPivotQuickSort {
+        DualPivotQuickSort {
+            sample,
+            cutoff,
+            stack: Vec::new(),
+            partition: None,
+            started: false,
+        }
+    }
+}
+
+impl<T> Sort<T> for DualPivotQuickSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            // the largest third is pushed first, so that the stack only ever holds the two
+            // smaller thirds of each range above it, which are at most half its length
+            let len = a.len();
+            self.stack.reserve(2 * (usize::BITS - len.leading_zeros()) as usize + 1);
+            if len > 1 {
+                self.stack.push(0..len);
+            }
+            self.started = true;
+        }
+
+        let partition = match &mut self.partition {
+            Some(partition) => partition,
+            None => match self.stack.pop() {
+                Some(range) => self.partition.insert(Partition::new(range, self.cutoff)),
+                None => return SortResult::Done,
+            },
+        };
+
+        if let Some(mut thirds) = partition.step(a, self.sample, events) {
+            self.partition = None;
+            thirds.sort_unstable_by_key(|third| Reverse(third.len()));
+            for third in thirds {
+                if third.len() > 1 {
+                    self.stack.push(third);
+                }
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if let Some(partition) = &self.partition {
+            annotations.ranges.push(partition.range.clone());
+            partition.annotate(annotations);
+        }
+    }
+}
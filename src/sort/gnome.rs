@@ -0,0 +1,29 @@
+use super::*;
+
+/// Gnome sort, which walks along the array swapping each element it finds out of order back a
+/// place and stepping back with it, then walks forward again. It's an insertion sort that walks
+/// back to where it left off one step at a time, rather than remembering.
+pub struct GnomeSort {
+    i: usize, // the gnome is looking at `i - 1` and `i`
+}
+
+impl GnomeSort {
+    pub fn new() -> GnomeSort {
+        GnomeSort { i: 1 }
+    }
+}
+
+impl<T> Sort<T> for GnomeSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if self.i >= a.len() {
+            return SortResult::Done;
+        }
+
+        if exchange(a, self.i - 1, self.i, events) && self.i > 1 {
+            self.i -= 1;
+        } else {
+            self.i += 1;
+        }
+        SortResult::Ok
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+/// Heapsort, which arranges the array into a max-heap and then repeatedly swaps the largest element
+/// to the end of the heap, sifting the new root back down. Hybrid sorts also use it to sort single
+/// ranges.
+pub struct HeapSort {
+    start: usize,
+    end: usize, // of the heap; everything from here to the end of the range is sorted
+    next: usize, // while building, nodes from `start` up to here are still to be sifted down
+    sift: Option<usize>, // node being sifted down
+    building: bool,
+    started: bool,
+}
+
+impl HeapSort {
+    pub fn new() -> HeapSort {
+        HeapSort {
+            started: false,
+            ..HeapSort::over(0..0)
+        }
+    }
+
+    /// Heapsorts only `range`, leaving the rest of the array alone.
+    pub fn over(range: Range<usize>) -> HeapSort {
+        HeapSort {
+            start: range.start,
+            end: range.end,
+            next: range.start + range.len() / 2,
+            sift: None,
+            building: true,
+            started: true,
+        }
+    }
+
+    /// The part of the range that is still a heap.
+    pub fn heap(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl<T> Sort<T> for HeapSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            *self = HeapSort::over(0..a.len());
+        }
+
+        loop {
+            if let Some(node) = self.sift {
+                let left = self.start + 2 * (node - self.start) + 1;
+                if left >= self.end {
+                    self.sift = None;
+                    continue;
+                }
+
+                let mut child = left;
+                if left + 1 < self.end {
+                    events.compare(left, left + 1);
+                    if a[left] < a[left + 1] {
+                        child = left + 1;
+                    }
+                }
+                events.compare(node, child);
+                if a[node] < a[child] {
+                    swap(a, node, child);
+                    events.swap(node, child);
+                    self.sift = Some(child);
+                } else {
+                    self.sift = None;
+                }
+                return SortResult::Ok;
+            } else if self.building {
+                // leaves are heaps already, so building starts from the last node with children
+                if self.next > self.start {
+                    self.next -= 1;
+                    self.sift = Some(self.next);
+                } else {
+                    self.building = false;
+                }
+            } else if self.end - self.start > 1 {
+                self.end -= 1;
+                swap(a, self.start, self.end);
+                events.swap(self.start, self.end);
+                self.sift = Some(self.start);
+                return SortResult::Ok;
+            } else {
+                return SortResult::Done;
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if !self.started {
+            return;
+        }
+        annotations.ranges.push(self.heap());
+        let phase = if self.building { "building the heap" } else { "taking the largest" };
+        annotations.stats.push(("phase", phase.to_string()));
+    }
+}
@@ -1,27 +1,44 @@
 use super::*;
 
+/// Insertion sort, optionally over every `gap`th element only, as used by shell sort.
 pub struct InsertionSort {
+    gap: usize,
     i: usize,
     j: usize,
 }
 
 impl InsertionSort {
     pub fn new() -> InsertionSort {
+        InsertionSort::with_gap(1)
+    }
+
+    pub fn with_gap(gap: usize) -> InsertionSort {
         InsertionSort {
-            i: 1,
-            j: 1,
+            gap,
+            i: gap,
+            j: gap,
         }
     }
+
+    /// The first index and stride of the elements currently being sorted together.
+    pub fn chain(&self) -> (usize, usize) {
+        (self.i % self.gap, self.gap)
+    }
 }
 
 impl<T> Sort<T> for InsertionSort where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        let gap = self.gap;
         if self.i < a.len() {
-            if self.j > 0 && a[self.j - 1] > a[self.j] {
-                let j = self.j;
-                swap(a, j, j - 1);
-                self.j -= 1;
-                SortResult::Swap(vec![j, j - 1])
+            let j = self.j;
+            if j >= gap {
+                events.compare(j - gap, j);
+            }
+            if j >= gap && a[j - gap] > a[j] {
+                swap(a, j, j - gap);
+                events.swap(j, j - gap);
+                self.j -= gap;
+                SortResult::Ok
             } else {
                 self.i += 1;
                 self.j = self.i;
@@ -31,4 +48,4 @@ impl<T> Sort<T> for InsertionSort where T: PartialOrd + Clone {
             SortResult::Done
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,135 @@
+use rand::rngs::StdRng;
+
+use super::{quicksort::{stack_depth, Partition, Rules}, *};
+
+/// Introsort, as used by most C++ standard libraries: quicksort, except that ranges still being
+/// split past a depth limit of some multiple of log₂ n are heapsorted instead, which bounds the
+/// worst case at O(n log n). Short ranges are insertion sorted.
+pub struct IntroSort {
+    rules: Rules,
+    factor: usize, // the depth limit, in multiples of log₂ n
+    limit: usize,
+    stack: Vec<(Range<usize>, usize)>, // ranges still to be sorted, with the depth they're at
+    task: Option<Task>,
+    heapsorted: usize, // ranges that hit the depth limit
+    started: bool,
+}
+
+enum Task {
+    Partition(Partition, usize), // at this depth
+    Heap(HeapSort),
+}
+
+impl IntroSort {
+    pub const PARAMS: [Param; 3] = [
+        Param {
+            name: "Pivot",
+            kind: Kind::Choice(&["first", "last", "middle", "random", "median of three"]),
+            default: Value::Choice(4),
+        },
+        Param {
+            name: "Insertion cutoff",
+            kind: Kind::Int { min: 0, max: 32 },
+            default: Value::Int(16),
+        },
+        Param {
+            name: "Depth (× log₂ n)",
+            kind: Kind::Int { min: 0, max: 4 },
+            default: Value::Int(2),
+        },
+    ];
+
+    pub fn new(pivot: PivotRule, cutoff: usize, factor: usize, rng: StdRng) -> IntroSort {
+        IntroSort {
+            rules: Rules::new(Scheme::Hoare, pivot, cutoff, rng),
+            factor,
+            limit: 0,
+            stack: Vec::new(),
+            task: None,
+            heapsorted: 0,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, len: usize) {
+        self.limit = self.factor * len.checked_ilog2().unwrap_or(0) as usize;
+        self.stack.reserve(stack_depth(len));
+        self.push(0..len, 0);
+        self.started = true;
+    }
+
+    fn push(&mut self, range: Range<usize>, depth: usize) {
+        if range.len() > 1 {
+            self.stack.push((range, depth));
+        }
+    }
+
+    fn next_task(&mut self, range: Range<usize>, depth: usize) -> Task {
+        if depth >= self.limit && range.len() > self.rules.cutoff {
+            self.heapsorted += 1;
+            Task::Heap(HeapSort::over(range))
+        } else {
+            Task::Partition(Partition::new(range, &self.rules), depth)
+        }
+    }
+}
+
+impl<T> Sort<T> for IntroSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a.len());
+        }
+
+        let task = match &mut self.task {
+            Some(task) => task,
+            None => match self.stack.pop() {
+                Some((range, depth)) => {
+                    let task = self.next_task(range, depth);
+                    self.task.insert(task)
+                }
+                None => return SortResult::Done,
+            },
+        };
+
+        match task {
+            Task::Partition(partition, depth) => {
+                if let Some((left, right)) = partition.step(a, &mut self.rules, events) {
+                    let depth = *depth + 1;
+                    self.task = None;
+                    let (smaller, larger) = if left.len() < right.len() {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                    self.push(larger, depth);
+                    self.push(smaller, depth);
+                }
+            }
+            Task::Heap(heap) => {
+                if let SortResult::Done = heap.step(a, events) {
+                    self.task = None;
+                }
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let depth = match &self.task {
+            Some(Task::Partition(partition, depth)) => {
+                annotations.pivots.extend(partition.pivot());
+                annotations.ranges.push(partition.range.clone());
+                annotations.methods.push((partition.range.clone(), partition.method()));
+                *depth
+            }
+            Some(Task::Heap(heap)) => {
+                annotations.ranges.push(heap.heap());
+                annotations.methods.push((heap.heap(), Method::Heap));
+                self.limit
+            }
+            None => return,
+        };
+        annotations.stats.push(("depth", format!("{depth} (limit {})", self.limit)));
+        annotations.stats.push(("heapsorted ranges", self.heapsorted.to_string()));
+    }
+}
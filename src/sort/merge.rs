@@ -0,0 +1,317 @@
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Merging {
+    Buffer, // copying the left run out to a buffer and merging it back, the usual way
+    SymMerge, // Kim and Kutzner's, which swaps the middle of the two runs with a rotation
+    Block, // dropping blocks of the left run into the right one with rotations
+}
+
+impl Merging {
+    pub const ALL: [Merging; 3] = [Merging::Buffer, Merging::SymMerge, Merging::Block];
+}
+
+enum Merge {
+    Copy { i: usize }, // copying the left run to the buffer, up to `i`
+    Buffer { i: usize, j: usize, k: usize }, // merging `buffer[i..]` and `a[j..]` into `a[k..]`
+    Rotations, // `symmerge` is merging the runs
+    // what's left of the left run is `t..end`, and of the right run `end..`. While searching,
+    // the right run's elements less than the last of the block starting at `t` are being counted
+    Block { t: usize, end: usize, search: Option<Range<usize>>, local: bool },
+}
+
+/// Bottom-up merge sort, which merges neighbouring runs of 1 element, then 2, then 4, and so on.
+/// Merging with a buffer takes a linear number of writes per pass, but needs extra memory for as
+/// much as the left run. The other two merges work in place, using rotations, at the cost of
+/// moving elements around more.
+pub struct MergeSort<T> {
+    merging: Merging,
+    width: usize, // of the runs being merged in this pass
+    runs: [usize; 3], // the start of the left and right runs being merged, and the end of the right
+    merge: Option<Merge>,
+    buffer: Vec<T>,
+    symmerge: SymMerge,
+    len: usize,
+    started: bool,
+}
+
+impl<T> MergeSort<T> {
+    pub const PARAMS: [Param; 1] = [Param {
+        name: "Merge",
+        kind: Kind::Choice(&["buffer", "SymMerge", "block merge"]),
+        default: Value::Choice(0),
+    }];
+
+    pub fn new(merging: Merging) -> MergeSort<T> {
+        MergeSort {
+            merging,
+            width: 1,
+            runs: [0; 3],
+            merge: None,
+            buffer: Vec::new(),
+            symmerge: SymMerge::new(),
+            len: 0,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, len: usize) {
+        self.len = len;
+        if self.merging == Merging::Buffer {
+            // the left run of the last merge can be most of the array
+            self.buffer.reserve(len);
+        } else {
+            self.symmerge.stack.reserve(2 * (usize::BITS - len.leading_zeros()) as usize + 2);
+        }
+        self.started = true;
+    }
+
+    /// Moves on to the next pair of runs, returning false once there are none left.
+    fn next_merge(&mut self) -> bool {
+        let [_, _, mut lo] = self.runs;
+        loop {
+            if self.width >= self.len {
+                return false;
+            }
+            // a run left over at the end of a pass has nothing to merge with until a later one
+            if lo + self.width >= self.len {
+                (lo, self.width) = (0, self.width * 2);
+                continue;
+            }
+            let (mid, hi) = (lo + self.width, (lo + 2 * self.width).min(self.len));
+            self.runs = [lo, mid, hi];
+            self.merge = Some(match self.merging {
+                Merging::Buffer => {
+                    self.buffer.clear();
+                    Merge::Copy { i: lo }
+                }
+                Merging::SymMerge => {
+                    self.symmerge.stack.push([lo, mid, hi]);
+                    Merge::Rotations
+                }
+                Merging::Block => Merge::Block { t: lo, end: mid, search: None, local: false },
+            });
+            return true;
+        }
+    }
+}
+
+impl<T> Sort<T> for MergeSort<T> where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a.len());
+        }
+        if self.merge.is_none() && !self.next_merge() {
+            return SortResult::Done;
+        }
+
+        let [lo, mid, hi] = self.runs;
+        let block = (hi - lo).isqrt();
+        let Some(merge) = &mut self.merge else {
+            unreachable!("a merge was just started");
+        };
+        let finished = match merge {
+            Merge::Copy { i } => {
+                self.buffer.push(a[*i].clone());
+                events.read(*i);
+                *i += 1;
+                if *i == mid {
+                    *merge = Merge::Buffer { i: 0, j: mid, k: lo };
+                }
+                false
+            }
+            Merge::Buffer { i, j, k } => {
+                // ties go to the buffer, which holds the left run, to keep the sort stable
+                if *j < hi {
+                    events.compare_held(*j);
+                }
+                if *j < hi && a[*j] < self.buffer[*i] {
+                    a[*k] = a[*j].clone();
+                    *j += 1;
+                } else {
+                    a[*k] = self.buffer[*i].clone();
+                    *i += 1;
+                }
+                events.write(*k);
+                *k += 1;
+                // anything left in the right run is already in place
+                *i == self.buffer.len()
+            }
+            Merge::Rotations => self.symmerge.step(a, events),
+            Merge::Block { t, end, search, local } => {
+                if *local {
+                    *local = !self.symmerge.step(a, events);
+                } else {
+                    let x = (*t + block).min(*end); // the end of the block
+                    let range = search.get_or_insert(*end..hi);
+                    if range.start < range.end {
+                        let c = (range.start + range.end) / 2;
+                        events.compare(c, x - 1);
+                        if a[c] < a[x - 1] {
+                            range.start = c + 1;
+                        } else {
+                            range.end = c;
+                        }
+                    } else {
+                        // the right run's elements that belong before the end of the block are
+                        // moved in front of the rest of the left run, then merged with the block
+                        let p = range.start;
+                        *search = None;
+                        if p > *end {
+                            rotate(a, x..p, *end, events);
+                            self.symmerge.stack.push([*t, x, x + p - *end]);
+                            *local = true;
+                        }
+                        (*t, *end) = (x + p - *end, p);
+                    }
+                }
+                !*local && search.is_none() && (*t == *end || *end == hi)
+            }
+        };
+
+        if finished {
+            self.merge = None;
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let Some(merge) = &self.merge else {
+            return;
+        };
+        let [lo, _, hi] = self.runs;
+        annotations.ranges.push(lo..hi);
+
+        // runs already merged in this pass are twice as long as the ones still to be
+        let merged = (0..lo).step_by(2 * self.width).map(|start| start..start + 2 * self.width);
+        let unmerged = (lo..self.len).step_by(self.width)
+            .map(|start| start..(start + self.width).min(self.len));
+        annotations.runs.extend(merged.chain(unmerged));
+
+        if let Merge::Block { t, end, search: Some(_), .. } = *merge {
+            annotations.pivots.push((t + (hi - lo).isqrt()).min(end) - 1);
+        }
+        let memory = match self.merging {
+            Merging::Buffer => format!("{} elements", self.buffer.len()),
+            Merging::SymMerge | Merging::Block => "none".to_string(),
+        };
+        annotations.stats.push(("width", self.width.to_string()));
+        annotations.stats.push(("extra memory", memory));
+    }
+}
+
+enum Probe {
+    // the left run is a single element, which goes before the right run's first element that
+    // isn't less than it
+    First { l: usize, r: usize },
+    // the right run is a single element, which goes before the left run's first element that is
+    // greater than it
+    Last { l: usize, r: usize },
+    // the left run's elements from `start` on are to be swapped with the right run's up to
+    // `mid + m - start`, where `m` is the middle of both runs
+    Split { l: usize, r: usize },
+}
+
+/// Merges pairs of neighbouring sorted runs in place with Kim and Kutzner's SymMerge, as Go's
+/// stable sort does. A binary search finds how much of the end of the left run and the start of
+/// the right run to swap, so that each half of the result is a merge of two shorter runs, and a
+/// rotation swaps them.
+struct SymMerge {
+    stack: Vec<[usize; 3]>, // merges still to do, as the start of each run and the end of the right
+    task: Option<([usize; 3], Probe)>,
+}
+
+impl SymMerge {
+    fn new() -> SymMerge {
+        SymMerge {
+            stack: Vec::new(),
+            task: None,
+        }
+    }
+
+    /// Makes one comparison or rotation, returning true once every merge on the stack is done.
+    fn step<T>(&mut self, a: &mut [T], events: &mut dyn Events) -> bool where T: PartialOrd {
+        let ([lo, mid, hi], probe) = match &mut self.task {
+            Some(task) => task,
+            None => match self.stack.pop() {
+                Some([lo, mid, hi]) => {
+                    let probe = if mid - lo == 1 {
+                        Probe::First { l: mid, r: hi }
+                    } else if hi - mid == 1 {
+                        Probe::Last { l: lo, r: mid }
+                    } else {
+                        let m = (lo + hi) / 2;
+                        if mid > m {
+                            Probe::Split { l: mid + m - hi, r: m }
+                        } else {
+                            Probe::Split { l: lo, r: mid }
+                        }
+                    };
+                    self.task.insert(([lo, mid, hi], probe))
+                }
+                None => return true,
+            },
+        };
+        let (lo, mid, hi) = (*lo, *mid, *hi);
+
+        match probe {
+            Probe::First { l, r } if *l < *r => {
+                let c = (*l + *r) / 2;
+                events.compare(lo, c);
+                if a[c] < a[lo] {
+                    *l = c + 1;
+                } else {
+                    *r = c;
+                }
+            }
+            &mut Probe::First { l, .. } => {
+                if l - 1 > lo {
+                    rotate(a, lo..l, lo + 1, events);
+                }
+                self.task = None;
+            }
+            Probe::Last { l, r } if *l < *r => {
+                let c = (*l + *r) / 2;
+                events.compare(c, mid);
+                if a[mid] < a[c] {
+                    *r = c;
+                } else {
+                    *l = c + 1;
+                }
+            }
+            &mut Probe::Last { l, .. } => {
+                if l < mid {
+                    rotate(a, l..hi, mid, events);
+                }
+                self.task = None;
+            }
+            Probe::Split { l, r } if *l < *r => {
+                // compares pairs placed symmetrically around the middle of both runs
+                let c = (*l + *r) / 2;
+                let p = mid + (lo + hi) / 2 - 1 - c;
+                events.compare(c, p);
+                if a[p] < a[c] {
+                    *r = c;
+                } else {
+                    *l = c + 1;
+                }
+            }
+            &mut Probe::Split { l: start, .. } => {
+                let m = (lo + hi) / 2;
+                let end = mid + m - start;
+                if start < mid && mid < end {
+                    rotate(a, start..end, mid, events);
+                }
+                // the left half is pushed last, to be merged first
+                if m < end && end < hi {
+                    self.stack.push([m, end, hi]);
+                }
+                if lo < start && start < m {
+                    self.stack.push([lo, start, m]);
+                }
+                self.task = None;
+            }
+        }
+        self.task.is_none() && self.stack.is_empty()
+    }
+}
@@ -2,44 +2,252 @@
 // function call, rather than resulting in a fully sorted list, only progresses the sort by one
 // "step"... this makes it easier to integrate with the UI render loop.
 
-use std::{cell::RefCell, rc::Rc};
+use std::{cmp::Ordering, ops::Range};
+
+use rand::Rng;
 
 mod bogosort;
 pub use bogosort::BogoSort;
 
+mod bogobogo;
+pub use bogobogo::BogoBogoSort;
+
+mod bozo;
+pub use bozo::BozoSort;
+
 mod bubblesort;
 pub use bubblesort::BubbleSort;
 
+mod budget;
+pub use budget::{Budget, BUDGET, BUDGETS};
+
+mod bucket;
+pub use bucket::BucketSort;
+
 mod cocktail;
 pub use cocktail::CocktailShakerSort;
 
+mod comb;
+pub use comb::CombSort;
+
+mod counting;
+pub use counting::CountingSort;
+
+mod cycle;
+pub use cycle::CycleSort;
+
+mod dualpivot;
+pub use dualpivot::{DualPivotQuickSort, PivotSample};
+
+mod gnome;
+pub use gnome::GnomeSort;
+
+mod heap;
+pub use heap::HeapSort;
+
 mod insertion;
 pub use insertion::InsertionSort;
 
+mod introsort;
+pub use introsort::IntroSort;
+
+mod merge;
+pub use merge::{MergeSort, Merging};
+
+mod network;
+pub use network::{Comparators, Network, NetworkSort};
+
+mod pancake;
+pub use pancake::PancakeSort;
+
+mod pdqsort;
+pub use pdqsort::PdqSort;
+
 mod quicksort;
-pub use quicksort::QuickSort;
+pub use quicksort::{ParallelQuickSort, PivotRule, QuickSort, Scheme};
+
+mod radix;
+pub use radix::RadixSort;
 
 mod selection;
 pub use selection::SelectionSort;
 
+mod shell;
+pub use shell::{GapSequence, ShellSort};
+
+mod slowsort;
+pub use slowsort::SlowSort;
+
+mod smooth;
+pub use smooth::SmoothSort;
+
+mod stooge;
+pub use stooge::StoogeSort;
+
+mod timsort;
+pub use timsort::TimSort;
+
 pub enum SortResult {
     Done,
-    Swap(Vec<usize>),
     Ok,
+    Abandoned, // gave up before the array was sorted
 }
 
 pub trait Sort<T: PartialOrd + Clone> {
-    // represents a single step of an algorithm:
-    fn step(&mut self, a: &mut [T]) -> SortResult;
+    // represents a single step of an algorithm. Anything the step did to the array is reported to
+    // `events` as it happens, so that stepping never has to allocate.
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult;
+
+    // describes where the sort is up to, for the chart to highlight
+    fn annotate(&self, _annotations: &mut Annotations) {}
 }
 
-fn is_sorted<T>(data: &[T]) -> bool where T: PartialOrd {
-    for i in 0..data.len() - 2 {
-        if data[i] > data[i + 2] {
-            return false;
+/// Something an algorithm can be configured with. Sorts are created with a value for each of their
+/// params, always of the param's own kind.
+pub struct Param {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub default: Value,
+}
+
+pub enum Kind {
+    Choice(&'static [&'static str]),
+    Int { min: i64, max: i64 },
+    Tenths { min: i64, max: i64 }, // a number with one decimal place, whose values count tenths
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Value {
+    Choice(usize), // index into the choices
+    Int(i64),
+}
+
+impl Value {
+    pub fn choice(self) -> usize {
+        match self {
+            Value::Choice(i) => i,
+            _ => panic!("expected a choice, got {self:?}"),
+        }
+    }
+
+    pub fn int(self) -> i64 {
+        match self {
+            Value::Int(n) => n,
+            _ => panic!("expected an integer, got {self:?}"),
+        }
+    }
+}
+
+impl Param {
+    /// The next or previous value after `value`. Choices wrap around; numbers stop at the ends of
+    /// their range.
+    pub fn adjust(&self, value: Value, forward: bool) -> Value {
+        match (&self.kind, value) {
+            (Kind::Choice(choices), Value::Choice(i)) => Value::Choice(if forward {
+                (i + 1) % choices.len()
+            } else {
+                (i + choices.len() - 1) % choices.len()
+            }),
+            (&Kind::Int { min, max } | &Kind::Tenths { min, max }, Value::Int(n)) => {
+                Value::Int(if forward { n + 1 } else { n - 1 }.clamp(min, max))
+            }
+            _ => value,
+        }
+    }
+
+    pub fn format(&self, value: Value) -> String {
+        match (&self.kind, value) {
+            (Kind::Choice(choices), Value::Choice(i)) => choices[i].to_string(),
+            (Kind::Tenths { .. }, Value::Int(n)) => format!("{}.{}", n / 10, n % 10),
+            (_, Value::Int(n)) => n.to_string(),
+            (_, value) => format!("{value:?}"),
+        }
+    }
+}
+
+/// The state of a sort in between steps. Unlike events, these are only collected once per batch of
+/// steps, so they're free to allocate.
+#[derive(Clone, Default)]
+pub struct Annotations {
+    pub pivots: Vec<usize>,
+    pub ranges: Vec<Range<usize>>, // the parts of the array currently being worked on
+    pub chain: Option<(usize, usize)>, // first index and stride of elements being sorted together
+    pub stats: Vec<(&'static str, String)>, // extra lines for the stats panel, e.g. the current gap
+    pub buckets: Vec<Vec<usize>>, // contents of each bucket, for sorts that distribute elements
+    pub histogram: Vec<usize>, // e.g. the number of times each value has been counted
+    pub methods: Vec<(Range<usize>, Method)>, // for hybrid sorts, which algorithm has which range
+    pub runs: Vec<Range<usize>>, // sorted runs waiting to be merged, oldest first
+    pub regions: Vec<(Range<usize>, Region)>, // where partitioned elements have been put so far
+    pub network: Option<Comparators>, // for sorting networks, which comparisons they make
+    pub heaps: Vec<Range<usize>>, // for smoothsort, the heaps of its forest, each rooted at the end
+    pub budget: Option<(u64, u64)>, // steps taken and allowed, for sorts that give up
+}
+
+/// The parts a range is partitioned into around two pivots, each of which is drawn in its own
+/// color.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Region {
+    Less,
+    Between,
+    Greater,
+}
+
+impl Region {
+    pub const ALL: [Region; 3] = [Region::Less, Region::Between, Region::Greater];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Less => "less",
+            Region::Between => "between",
+            Region::Greater => "greater",
+        }
+    }
+}
+
+/// The algorithms a hybrid sort switches between, each of which is drawn in its own color.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Method {
+    Partition,
+    Heap,
+    Insertion,
+}
+
+impl Method {
+    pub const ALL: [Method; 3] = [Method::Partition, Method::Heap, Method::Insertion];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::Partition => "quicksort",
+            Method::Heap => "heapsort",
+            Method::Insertion => "insertion sort",
         }
     }
-    true
+}
+
+/// Receives the operations performed during a step, in the order they happened.
+pub trait Events {
+    fn swap(&mut self, i: usize, j: usize);
+    fn compare(&mut self, i: usize, j: usize);
+    fn compare_held(&mut self, i: usize); // comparing an element with one held outside the array
+    fn read(&mut self, i: usize); // looking at an element without comparing it, e.g. for its digits
+    fn write(&mut self, i: usize); // overwriting an element, e.g. from a buffer
+    fn reverse(&mut self, range: Range<usize>); // reversing a whole range at once
+    fn rotate(&mut self, range: Range<usize>, mid: usize); // moving `mid..` to the start of `range`
+}
+
+/// Ignores all events, for running a sort when nobody is watching.
+impl Events for () {
+    fn swap(&mut self, _: usize, _: usize) {}
+    fn compare(&mut self, _: usize, _: usize) {}
+    fn compare_held(&mut self, _: usize) {}
+    fn read(&mut self, _: usize) {}
+    fn write(&mut self, _: usize) {}
+    fn reverse(&mut self, _: Range<usize>) {}
+    fn rotate(&mut self, _: Range<usize>, _: usize) {}
+}
+
+fn is_sorted<T>(data: &[T]) -> bool where T: PartialOrd {
+    data.windows(2).all(|w| w[0] <= w[1])
 }
 
 fn swap<T>(a: &mut [T], i: usize, j: usize) where T: Clone {
@@ -48,29 +256,192 @@ fn swap<T>(a: &mut [T], i: usize, j: usize) where T: Clone {
     a[i] = temp;
 }
 
-pub fn shuffle<T>(a: &mut [T]) where T: Clone {
+/// Reverses `range` in a single operation, rather than as a series of swaps.
+fn reverse<T>(a: &mut [T], range: Range<usize>, events: &mut dyn Events) {
+    a[range.clone()].reverse();
+    events.reverse(range);
+}
+
+/// Moves the elements from `mid` to the end of `range` to its start, in a single operation.
+fn rotate<T>(a: &mut [T], range: Range<usize>, mid: usize, events: &mut dyn Events) {
+    a[range.clone()].rotate_left(mid - range.start);
+    events.rotate(range, mid);
+}
+
+/// Compares the elements at `i` and `j`, where `i < j`, and swaps them if they're out of order.
+/// This is all an exchange sort ever does. Returns whether they were swapped.
+fn exchange<T>(a: &mut [T], i: usize, j: usize, events: &mut dyn Events) -> bool
+where T: PartialOrd + Clone {
+    events.compare(i, j);
+    if a[i] > a[j] {
+        swap(a, i, j);
+        events.swap(i, j);
+        true
+    } else {
+        false
+    }
+}
+
+/// What's left in `buckets` while they're being written back, which has reached element `j` of
+/// `bucket`. Buckets that have already been written back are empty.
+fn unwritten(buckets: &[Vec<usize>], bucket: usize, j: usize) -> Vec<Vec<usize>> {
+    buckets.iter().enumerate()
+        .map(|(b, contents)| match b.cmp(&bucket) {
+            Ordering::Less => Vec::new(),
+            Ordering::Equal => contents[j..].to_vec(),
+            Ordering::Greater => contents.clone(),
+        })
+        .collect()
+}
+
+pub fn shuffle<T>(a: &mut [T], rng: &mut impl Rng) where T: Clone {
     for i in 0..a.len() {
-        let random = rand::random::<usize>() % a.len();
+        let random = rng.gen_range(0..a.len());
         let temp = a[random].clone();
         a[random] = a[i].clone();
         a[i] = temp;
     }
 }
 
+// the original tests run their sorts with a `match` rather than `if let`, and are kept as written
 #[cfg(test)]
+#[allow(clippy::single_match)]
 mod tests {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
     use crate::ARRAY_LEN;
 
+    // counts allocations per thread, so that tests running in parallel don't see each other's
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn rng() -> StdRng {
+        StdRng::from_entropy()
+    }
+
+    /// Inputs that tend to trip sorts up: shuffled, reversed, sorted, few unique and all equal.
+    fn inputs() -> [[usize; ARRAY_LEN]; 5] {
+        let mut shuffled: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
+        shuffle(&mut shuffled, &mut rand::thread_rng());
+        [
+            shuffled,
+            core::array::from_fn(|i| ARRAY_LEN - i), // reversed
+            core::array::from_fn(|i| i), // sorted
+            core::array::from_fn(|i| i % 7), // few unique
+            [1; ARRAY_LEN],
+        ]
+    }
+
+    /// Steps `sort` until it's done with `array`, returning how many steps that took.
+    fn run_to_done<T>(sort: &mut dyn Sort<T>, array: &mut [T]) -> usize
+    where T: PartialOrd + Clone {
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            match sort.step(array, &mut ()) {
+                SortResult::Done => return steps,
+                SortResult::Ok => (),
+                SortResult::Abandoned => panic!("gave up after {steps} steps"),
+            }
+        }
+    }
+
+    #[test]
+    fn steps_dont_allocate() {
+        let sorts: [(&str, Box<dyn Sort<usize>>); 32] = [
+            ("bogosort", Box::new(BogoSort::new(rng()))),
+            ("bozo sort", Box::new(BozoSort::new(rng()))),
+            ("bogobogosort", Box::new(Budget::new(BogoBogoSort::new(rng()), 1_000))),
+            ("stooge sort", Box::new(StoogeSort::new())),
+            ("slowsort", Box::new(SlowSort::new())),
+            ("bubble sort", Box::new(BubbleSort::new())),
+            ("cocktail shaker sort", Box::new(CocktailShakerSort::new())),
+            ("comb sort", Box::new(CombSort::new(1.3))),
+            ("gnome sort", Box::new(GnomeSort::new())),
+            ("cycle sort", Box::new(CycleSort::new())),
+            ("pancake sort", Box::new(PancakeSort::new())),
+            ("insertion sort", Box::new(InsertionSort::new())),
+            ("quicksort", Box::new(QuickSort::new(Scheme::Lomuto, PivotRule::Last, 0, rng()))),
+            ("Hoare quicksort", Box::new(QuickSort::new(Scheme::Hoare, PivotRule::Random, 8, rng()))),
+            ("three-way quicksort", Box::new(QuickSort::new(Scheme::ThreeWay, PivotRule::MedianOfThree, 0, rng()))),
+            ("parallel quicksort", Box::new(ParallelQuickSort::new(Scheme::Lomuto, PivotRule::Last, 0, rng()))),
+            ("dual-pivot quicksort", Box::new(DualPivotQuickSort::new(PivotSample::Thirds, 0))),
+            ("heapsort", Box::new(HeapSort::new())),
+            ("smoothsort", Box::new(SmoothSort::new())),
+            ("introsort", Box::new(IntroSort::new(PivotRule::First, 16, 1, rng()))),
+            ("pdqsort", Box::new(PdqSort::new(PivotRule::MedianOfThree, 24, rng()))),
+            ("counting sort", Box::new(CountingSort::new())),
+            ("bucket sort", Box::new(BucketSort::new(10))),
+            ("LSD radix sort", Box::new(RadixSort::lsd(10))),
+            ("MSD radix sort", Box::new(RadixSort::msd(4))),
+            ("selection sort", Box::new(SelectionSort::new())),
+            ("shell sort", Box::new(ShellSort::new(GapSequence::Ciura))),
+            ("timsort", Box::new(TimSort::new(8))),
+            ("merge sort", Box::new(MergeSort::new(Merging::Buffer))),
+            ("SymMerge sort", Box::new(MergeSort::new(Merging::SymMerge))),
+            ("block merge sort", Box::new(MergeSort::new(Merging::Block))),
+            ("bitonic sort", Box::new(NetworkSort::new(Network::Bitonic))),
+        ];
+
+        for (name, mut sort) in sorts {
+            let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
+            shuffle(&mut array, &mut rand::thread_rng());
+
+            // the first step may allocate, as that's when sorts learn how long the array is
+            sort.step(&mut array, &mut ());
+            let before = ALLOCATIONS.with(Cell::get);
+            for _ in 0..20_000 {
+                if let SortResult::Done = sort.step(&mut array, &mut ()) {
+                    break;
+                }
+            }
+            assert_eq!(ALLOCATIONS.with(Cell::get), before, "{name} allocated while stepping");
+        }
+    }
+
+    #[test]
+    fn sorted() {
+        // every neighbouring pair counts, not just elements two apart
+        assert!(!is_sorted(&[2, 1, 3]));
+        assert!(!is_sorted(&[1, 3, 2, 4]));
+        assert!(is_sorted(&[1, 2, 2, 3]));
+        // too short to be out of order
+        assert!(is_sorted::<usize>(&[]));
+        assert!(is_sorted(&[1]));
+    }
+
     #[test]
     #[ignore = "takes forever"]
     fn bogosort() {
         let mut array: [usize ; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
-        let mut sort = BogoSort {};
+        shuffle(&mut array, &mut rand::thread_rng());
+        let mut sort = BogoSort::new(rng());
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -79,14 +450,65 @@ mod tests {
         assert!(is_sorted(&array))
     }
 
+    #[test]
+    fn joke_sorts() {
+        // the hopeless ones only get a few elements
+        let sorts: [(&str, Box<dyn Sort<usize>>, usize); 4] = [
+            ("bozo sort", Box::new(BozoSort::new(rng())), 5),
+            ("bogobogosort", Box::new(BogoBogoSort::new(rng())), 5),
+            ("stooge sort", Box::new(StoogeSort::new()), ARRAY_LEN),
+            ("slowsort", Box::new(SlowSort::new()), 40),
+        ];
+
+        for (name, mut sort, len) in sorts {
+            let mut array: Vec<usize> = (1..=len).collect();
+            shuffle(&mut array, &mut rand::thread_rng());
+            loop {
+                if let SortResult::Done = sort.step(&mut array, &mut ()) {
+                    break;
+                }
+            }
+            assert!(is_sorted(&array), "{name} didn't sort");
+        }
+
+        // a seed repeats the same random swaps
+        let steps = |seed| {
+            let mut array = [5, 4, 3, 2, 1];
+            let mut sort = BozoSort::new(StdRng::seed_from_u64(seed));
+            let mut steps = 0;
+            while let SortResult::Ok = sort.step(&mut array, &mut ()) {
+                steps += 1;
+            }
+            steps
+        };
+        assert_eq!(steps(7), steps(7));
+    }
+
+    #[test]
+    fn budget() {
+        let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| ARRAY_LEN - i);
+        let mut sort = Budget::new(BogoSort::new(rng()), 100);
+        let mut steps = 1;
+        while let SortResult::Ok = sort.step(&mut array, &mut ()) {
+            steps += 1;
+        }
+        assert_eq!(steps, 100);
+
+        // a sort that finishes within its budget is done as usual
+        let mut array = [3, 1, 2];
+        let mut sort = Budget::new(StoogeSort::new(), 100);
+        while let SortResult::Ok = sort.step(&mut array, &mut ()) {}
+        assert_eq!(array, [1, 2, 3]);
+    }
+
     #[test]
     fn bubble_sort() {
         let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
+        shuffle(&mut array, &mut rand::thread_rng());
         let mut sort = BubbleSort::new();
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -98,11 +520,11 @@ mod tests {
     #[test]
     fn cocktail_shaker_sort() {
         let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
+        shuffle(&mut array, &mut rand::thread_rng());
         let mut sort = CocktailShakerSort::new();
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -111,14 +533,116 @@ mod tests {
         assert!(is_sorted(&array))
     }
 
+    #[test]
+    fn comb_sort() {
+        for shrink in [1.1, 1.3, 3.0] {
+            for mut array in inputs() {
+                run_to_done(&mut CombSort::new(shrink), &mut array);
+                assert!(is_sorted(&array), "shrink factor {shrink}")
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_sort() {
+        // counts writes, to check that only misplaced elements are written, once each
+        struct Writes(usize);
+
+        impl Events for Writes {
+            fn swap(&mut self, _: usize, _: usize) {
+                self.0 += 2;
+            }
+            fn compare(&mut self, _: usize, _: usize) {}
+            fn compare_held(&mut self, _: usize) {}
+            fn read(&mut self, _: usize) {}
+            fn write(&mut self, _: usize) {
+                self.0 += 1;
+            }
+            fn reverse(&mut self, _: Range<usize>) {}
+            fn rotate(&mut self, _: Range<usize>, _: usize) {}
+        }
+
+        let mut shuffled: [usize; ARRAY_LEN] = core::array::from_fn(|i| i % 23);
+        shuffle(&mut shuffled, &mut rand::thread_rng());
+        let inputs = [
+            shuffled,
+            core::array::from_fn(|i| i), // sorted
+            core::array::from_fn(|i| if i < 2 { 1 - i } else { i }), // one pair swapped
+        ];
+
+        for input in inputs {
+            let mut sorted = input;
+            sorted.sort();
+            let misplaced = input.iter().zip(&sorted).filter(|(x, y)| x != y).count();
+
+            let mut array = input;
+            let mut sort = CycleSort::new();
+            let mut writes = Writes(0);
+            loop {
+                if let SortResult::Done = sort.step(&mut array, &mut writes) {
+                    break;
+                }
+            }
+
+            assert_eq!(array, sorted);
+            assert_eq!(writes.0, misplaced);
+        }
+    }
+
+    #[test]
+    fn pancake_sort() {
+        // records the flips, which must all be of prefixes
+        struct Flips(Vec<Range<usize>>);
+
+        impl Events for Flips {
+            fn swap(&mut self, i: usize, j: usize) {
+                panic!("swapped {i} and {j}");
+            }
+            fn compare(&mut self, _: usize, _: usize) {}
+            fn compare_held(&mut self, _: usize) {}
+            fn read(&mut self, _: usize) {}
+            fn write(&mut self, i: usize) {
+                panic!("wrote to {i}");
+            }
+            fn reverse(&mut self, range: Range<usize>) {
+                self.0.push(range);
+            }
+            fn rotate(&mut self, range: Range<usize>, _: usize) {
+                panic!("rotated {range:?}");
+            }
+        }
+
+        let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i % 37);
+        shuffle(&mut array, &mut rand::thread_rng());
+        let mut sort = PancakeSort::new();
+        let mut flips = Flips(Vec::new());
+        loop {
+            if let SortResult::Done = sort.step(&mut array, &mut flips) {
+                break;
+            }
+        }
+
+        assert!(is_sorted(&array));
+        assert!(flips.0.iter().all(|flip| flip.start == 0));
+        assert!(flips.0.len() <= 2 * ARRAY_LEN);
+    }
+
+    #[test]
+    fn gnome_sort() {
+        for mut array in inputs() {
+            run_to_done(&mut GnomeSort::new(), &mut array);
+            assert!(is_sorted(&array))
+        }
+    }
+
     #[test]
     fn insertion_sort() {
         let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
+        shuffle(&mut array, &mut rand::thread_rng());
         let mut sort = InsertionSort::new();
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -130,11 +654,11 @@ mod tests {
     #[test]
     fn selection_sort() {
         let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
+        shuffle(&mut array, &mut rand::thread_rng());
         let mut sort = SelectionSort::new();
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -146,11 +670,11 @@ mod tests {
     #[test]
     fn quicksort() {
         let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
-        shuffle(&mut array);
-        let mut sort = QuickSort::new();
+        shuffle(&mut array, &mut rand::thread_rng());
+        let mut sort = QuickSort::new(Scheme::Lomuto, PivotRule::Last, 0, rng());
 
         loop {
-            match sort.step(&mut array) {
+            match sort.step(&mut array, &mut ()) {
                 SortResult::Done => break,
                 _ => ()
             }
@@ -158,4 +682,375 @@ mod tests {
 
         assert!(is_sorted(&array))
     }
+
+    #[test]
+    fn seeded_pivots() {
+        // the same seed picks the same random pivots, so a run can be repeated exactly
+        let mut shuffled: [usize; ARRAY_LEN] = core::array::from_fn(|i| i + 1);
+        shuffle(&mut shuffled, &mut rand::thread_rng());
+        let steps = |seed| {
+            let mut array = shuffled;
+            let rng = StdRng::seed_from_u64(seed);
+            let mut sort = QuickSort::new(Scheme::Lomuto, PivotRule::Random, 0, rng);
+            let mut steps = 0;
+            while let SortResult::Ok = sort.step(&mut array, &mut ()) {
+                steps += 1;
+            }
+            (steps, array)
+        };
+        assert_eq!(steps(7), steps(7));
+    }
+
+    #[test]
+    fn params() {
+        let [scheme, _, cutoff] = &QuickSort::PARAMS;
+        assert_eq!(scheme.adjust(Value::Choice(2), true), Value::Choice(0));
+        assert_eq!(scheme.adjust(Value::Choice(0), false), Value::Choice(2));
+        assert_eq!(scheme.format(Value::Choice(1)), "Hoare");
+        assert_eq!(cutoff.adjust(Value::Int(0), false), Value::Int(0));
+        assert_eq!(cutoff.adjust(Value::Int(31), true), Value::Int(32));
+        assert_eq!(cutoff.adjust(Value::Int(32), true), Value::Int(32));
+
+        let [shrink] = &CombSort::PARAMS;
+        assert_eq!(shrink.format(Value::Int(13)), "1.3");
+        assert_eq!(shrink.adjust(Value::Int(11), false), Value::Int(11));
+    }
+
+    #[test]
+    fn quicksort_options() {
+        for scheme in Scheme::ALL {
+            for pivot in PivotRule::ALL {
+                for (mut array, cutoff) in inputs().into_iter().zip([0, 4, 0, 16, 0]) {
+                    run_to_done(&mut QuickSort::new(scheme, pivot, cutoff, rng()), &mut array);
+                    assert!(is_sorted(&array), "{scheme:?} with {pivot:?} pivot")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_quicksort() {
+        for scheme in Scheme::ALL {
+            for pivot in PivotRule::ALL {
+                for (mut array, cutoff) in inputs().into_iter().zip([0, 4, 0, 16, 0]) {
+                    let mut sort = ParallelQuickSort::new(scheme, pivot, cutoff, rng());
+                    run_to_done(&mut sort, &mut array);
+                    assert!(is_sorted(&array), "{scheme:?} with {pivot:?} pivot")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dual_pivot_quicksort() {
+        for sample in PivotSample::ALL {
+            for (mut array, cutoff) in inputs().into_iter().zip([0, 4, 0, 16, 0]) {
+                run_to_done(&mut DualPivotQuickSort::new(sample, cutoff), &mut array);
+                assert!(is_sorted(&array), "{sample:?} pivots, cutoff {cutoff}")
+            }
+        }
+    }
+
+    #[test]
+    fn dual_pivot_regions() {
+        let mut array: [usize; ARRAY_LEN] = core::array::from_fn(|i| i % 31);
+        shuffle(&mut array, &mut rand::thread_rng());
+        let mut sort = DualPivotQuickSort::new(PivotSample::Ends, 0);
+
+        while let SortResult::Ok = sort.step(&mut array, &mut ()) {
+            let mut annotations = Annotations::default();
+            Sort::<usize>::annotate(&sort, &mut annotations);
+            let [p, q] = annotations.pivots[..] else {
+                continue;
+            };
+            let (p, q) = (array[p], array[q]);
+            // elements equal to the right pivot can end up on either side of it
+            for (range, region) in annotations.regions {
+                assert!(array[range].iter().all(|&x| match region {
+                    Region::Less => x < p,
+                    Region::Between => p <= x && x <= q,
+                    Region::Greater => x >= q,
+                }), "{region:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn sorting_networks() {
+        for network in Network::ALL {
+            // the merging networks are cut down from the next power of two
+            for len in 0..=40 {
+                let mut array: Vec<usize> = (0..len).map(|i| i % 13).collect();
+                shuffle(&mut array, &mut rand::thread_rng());
+                let mut sort = NetworkSort::new(network);
+
+                loop {
+                    if let SortResult::Done = sort.step(&mut array, &mut ()) {
+                        break;
+                    }
+                }
+
+                assert!(is_sorted(&array), "{network:?} network, {len} elements");
+            }
+        }
+    }
+
+    #[test]
+    fn network_layers() {
+        for network in Network::ALL {
+            for layer in network.layers(ARRAY_LEN) {
+                let mut touched = [false; ARRAY_LEN];
+                for (i, j) in layer {
+                    assert!(i < j && j < ARRAY_LEN, "{network:?} compares {i} with {j}");
+                    assert!(!touched[i] && !touched[j], "{network:?} uses {i} or {j} twice");
+                    (touched[i], touched[j]) = (true, true);
+                }
+            }
+        }
+
+        // log₂ n (log₂ n + 1) / 2 layers for the merging networks on powers of two
+        assert_eq!(Network::Bitonic.layers(64).len(), 21);
+        assert_eq!(Network::OddEvenMerge.layers(64).len(), 21);
+        assert_eq!(Network::OddEvenTransposition.layers(64).len(), 64);
+    }
+
+    #[test]
+    fn shell_sort() {
+        for sequence in GapSequence::ALL {
+            for mut array in inputs() {
+                run_to_done(&mut ShellSort::new(sequence), &mut array);
+                assert!(is_sorted(&array), "{sequence:?} gaps")
+            }
+        }
+    }
+
+    #[test]
+    fn gap_sequences() {
+        assert_eq!(GapSequence::Shell.gaps(100), [1, 3, 6, 12, 25, 50]);
+        assert_eq!(GapSequence::Knuth.gaps(100), [1, 4, 13]);
+        assert_eq!(GapSequence::Sedgewick.gaps(100), [1, 8, 23, 77]);
+        assert_eq!(GapSequence::Ciura.gaps(5000), [1, 4, 10, 23, 57, 132, 301, 701, 1750, 3937]);
+        assert_eq!(GapSequence::Tokuda.gaps(100), [1, 4, 9, 20, 46]);
+        assert_eq!(GapSequence::Ciura.gaps(1), [1]);
+    }
+
+    #[test]
+    fn radix_sort() {
+        for base in [2, 10, 64] {
+            for msd in [false, true] {
+                let new = || if msd { RadixSort::msd(base) } else { RadixSort::lsd(base) };
+                for input in inputs() {
+                    for len in [1, ARRAY_LEN] {
+                        let mut array = input[..len].to_vec();
+                        run_to_done(&mut new(), &mut array);
+                        assert!(is_sorted(&array), "base {base}, msd: {msd}, {:?}", &input[..len])
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn counting_sort() {
+        for mut array in inputs() {
+            run_to_done(&mut CountingSort::new(), &mut array);
+            assert!(is_sorted(&array))
+        }
+    }
+
+    #[test]
+    fn bucket_sort() {
+        for buckets in [2, 10, 64] {
+            for mut array in inputs() {
+                run_to_done(&mut BucketSort::new(buckets), &mut array);
+                assert!(is_sorted(&array), "{buckets} buckets")
+            }
+        }
+    }
+
+    #[test]
+    fn heapsort() {
+        for mut array in inputs() {
+            run_to_done(&mut HeapSort::new(), &mut array);
+            assert!(is_sorted(&array))
+        }
+    }
+
+    #[test]
+    fn smoothsort() {
+        let mut steps = Vec::new();
+        for input in inputs() {
+            for len in [0, 1, 2, 3, 10, ARRAY_LEN] {
+                let mut array = input[..len].to_vec();
+                let n = run_to_done(&mut SmoothSort::new(), &mut array);
+                assert!(is_sorted(&array), "{:?}", &input[..len]);
+                if len == ARRAY_LEN {
+                    steps.push(n);
+                }
+            }
+        }
+
+        // it adapts to sorted input, which takes no longer to sort than it does to read
+        let (shuffled, sorted) = (steps[0], steps[2]);
+        assert!(sorted * 2 < shuffled, "{sorted} steps sorted, {shuffled} shuffled");
+    }
+
+    #[test]
+    fn hybrid_sorts() {
+        for pivot in PivotRule::ALL {
+            for (input, cutoff) in inputs().into_iter().zip([0, 4, 16, 24, 0]) {
+                let sorts: [(&str, Box<dyn Sort<usize>>); 3] = [
+                    ("introsort", Box::new(IntroSort::new(pivot, cutoff, 2, rng()))),
+                    ("introsort without quicksort", Box::new(IntroSort::new(pivot, cutoff, 0, rng()))),
+                    ("pdqsort", Box::new(PdqSort::new(pivot, cutoff, rng()))),
+                ];
+                for (name, mut sort) in sorts {
+                    let mut array = input;
+                    run_to_done(sort.as_mut(), &mut array);
+                    assert!(is_sorted(&array), "{name} with {pivot:?} pivot, cutoff {cutoff}")
+                }
+            }
+        }
+    }
+
+    /// Runs `sort` over `array`, returning every method it annotated a range with along the way.
+    fn methods_used(mut sort: impl Sort<usize>, array: &mut [usize]) -> Vec<Method> {
+        let mut methods = Vec::new();
+        while let SortResult::Ok = sort.step(array, &mut ()) {
+            let mut annotations = Annotations::default();
+            sort.annotate(&mut annotations);
+            methods.extend(annotations.methods.into_iter().map(|(_, method)| method));
+        }
+        methods
+    }
+
+    #[test]
+    fn hybrid_fallbacks() {
+        // always picking the smallest element as the pivot makes quicksort quadratic, until the
+        // depth limit is reached
+        let mut sorted: [usize; ARRAY_LEN] = core::array::from_fn(|i| i);
+        let methods = methods_used(IntroSort::new(PivotRule::First, 16, 2, rng()), &mut sorted);
+        assert!(methods.contains(&Method::Heap));
+
+        // pdqsort notices that sorted input is already partitioned and only checks it
+        let mut sorted: [usize; ARRAY_LEN] = core::array::from_fn(|i| i);
+        let methods = methods_used(PdqSort::new(PivotRule::MedianOfThree, 24, rng()), &mut sorted);
+        assert!(!methods.contains(&Method::Heap));
+        assert!(methods.contains(&Method::Insertion));
+    }
+
+    #[test]
+    fn partial_insertion() {
+        // the median splits these evenly without moving anything, leaving halves a few swaps from
+        // sorted. Each half gets the full move limit to itself, so they're insertion sorted with
+        // one step per move more than it takes to check them when they're sorted already
+        let sorted: [usize; 33] = core::array::from_fn(|i| i);
+        let mut nearly_sorted = sorted;
+        for i in [0, 3, 6, 9, 12, 17, 20, 23, 26, 29] {
+            nearly_sorted.swap(i, i + 1);
+        }
+        let steps = |mut array: [usize; 33]| {
+            let mut sort = PdqSort::new(PivotRule::MedianOfThree, 0, rng());
+            let steps = run_to_done(&mut sort, &mut array);
+            assert!(is_sorted(&array));
+            steps
+        };
+        assert_eq!(steps(nearly_sorted), steps(sorted) + 10);
+    }
+
+    // compares by key only, so that stable sorts can be told apart from unstable ones
+    #[derive(Clone, Debug)]
+    struct Keyed(usize, usize);
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Keyed) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Keyed) -> Option<Ordering> {
+            self.0.partial_cmp(&other.0)
+        }
+    }
+
+    /// Tags each key with its position, so that elements with equal keys can still be told apart.
+    fn keyed(keys: impl IntoIterator<Item = usize>) -> Vec<Keyed> {
+        keys.into_iter().enumerate().map(|(i, key)| Keyed(key, i)).collect()
+    }
+
+    /// Whether `array` is sorted with elements of equal keys still in the order they started in,
+    /// as a stable sort leaves them.
+    fn stable(array: &[Keyed]) -> bool {
+        array.windows(2).all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1))
+    }
+
+    #[test]
+    fn timsort() {
+        let mut rng = rand::thread_rng();
+        let mut shuffled: [usize; ARRAY_LEN] = core::array::from_fn(|i| i % 23);
+        shuffle(&mut shuffled, &mut rng);
+        let inputs = [
+            shuffled,
+            core::array::from_fn(|i| ARRAY_LEN - i), // reversed
+            core::array::from_fn(|i| i), // sorted
+            core::array::from_fn(|i| i % 40), // ascending runs
+            core::array::from_fn(|i| (ARRAY_LEN - i) % 17), // descending runs
+            core::array::from_fn(|i| if i < ARRAY_LEN / 2 { i * 2 } else { i % 3 }), // lopsided
+            [1; ARRAY_LEN],
+        ];
+
+        for min_run in [2, 16, 64] {
+            for input in inputs {
+                let mut array = keyed(input);
+                run_to_done(&mut TimSort::new(min_run), &mut array);
+                assert!(stable(&array), "min run {min_run}: {array:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn merge_sort() {
+        // in-place merges must not write anything they haven't rotated
+        struct InPlace;
+
+        impl Events for InPlace {
+            fn swap(&mut self, _: usize, _: usize) {}
+            fn compare(&mut self, _: usize, _: usize) {}
+            fn compare_held(&mut self, i: usize) {
+                panic!("compared {i} with an element outside the array");
+            }
+            fn read(&mut self, _: usize) {}
+            fn write(&mut self, i: usize) {
+                panic!("wrote to {i}");
+            }
+            fn reverse(&mut self, _: Range<usize>) {}
+            fn rotate(&mut self, _: Range<usize>, _: usize) {}
+        }
+
+        let mut rng = rand::thread_rng();
+        for merging in Merging::ALL {
+            for len in [0, 1, 2, 3, 7, 33, ARRAY_LEN] {
+                for modulus in [1, 5, len.max(1)] {
+                    let mut keys: Vec<usize> = (0..len).map(|i| i % modulus).collect();
+                    shuffle(&mut keys, &mut rng);
+                    let mut array = keyed(keys);
+                    let mut sort = MergeSort::new(merging);
+
+                    loop {
+                        let done = if merging == Merging::Buffer {
+                            sort.step(&mut array, &mut ())
+                        } else {
+                            sort.step(&mut array, &mut InPlace)
+                        };
+                        if let SortResult::Done = done {
+                            break;
+                        }
+                    }
+
+                    assert!(stable(&array), "{merging:?} merges, {len} elements: {array:?}");
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
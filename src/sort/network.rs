@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Network {
+    Bitonic, // merges pairs of sorted halves, one of them flipped, with half-cleaners
+    OddEvenMerge, // Batcher's, which merges the odd and even elements of both halves separately
+    OddEvenTransposition, // alternately compares each even and each odd element with the next
+}
+
+impl Network {
+    pub const ALL: [Network; 3] = [
+        Network::Bitonic,
+        Network::OddEvenMerge,
+        Network::OddEvenTransposition,
+    ];
+
+    /// The comparators that sort `len` elements, in layers that can each be run in parallel. Every
+    /// comparator puts the smaller element at the lower index.
+    ///
+    /// The merging networks only work on powers of two, so they are built for the next one up as
+    /// if the missing elements were larger than any other. Those never move, so comparators
+    /// touching them are dropped.
+    pub fn layers(self, len: usize) -> Vec<Vec<(usize, usize)>> {
+        let size = len.next_power_of_two();
+        let mut layers = Vec::new();
+        match self {
+            Network::Bitonic => {
+                for block in (1..=size.ilog2()).map(|k| 1usize << k) {
+                    // rather than sorting every other block descending, the first layer of each
+                    // merge compares the halves mirrored
+                    layers.push(pairs(size, |i| i ^ (block - 1)));
+                    for distance in (0..block.ilog2() - 1).rev().map(|k| 1 << k) {
+                        layers.push(pairs(size, |i| i ^ distance));
+                    }
+                }
+            }
+            Network::OddEvenMerge => {
+                for block in (0..size.ilog2()).map(|k| 1usize << k) {
+                    for distance in (0..=block.ilog2()).rev().map(|k| 1 << k) {
+                        let mut layer = Vec::new();
+                        for j in (distance % block..size - distance).step_by(2 * distance) {
+                            for i in j..(j + distance).min(size - distance) {
+                                // only within the pair of blocks being merged
+                                if i / (2 * block) == (i + distance) / (2 * block) {
+                                    layer.push((i, i + distance));
+                                }
+                            }
+                        }
+                        layers.push(layer);
+                    }
+                }
+            }
+            Network::OddEvenTransposition => {
+                for parity in (0..len).map(|k| k % 2) {
+                    let pairs = (parity..len.saturating_sub(1)).step_by(2).map(|i| (i, i + 1));
+                    layers.push(pairs.collect());
+                }
+            }
+        }
+
+        for layer in &mut layers {
+            layer.retain(|&(_, j)| j < len);
+        }
+        layers.retain(|layer| !layer.is_empty());
+        layers
+    }
+}
+
+/// Pairs each of `size` elements with `partner(i)`, lower index first.
+fn pairs(size: usize, partner: impl Fn(usize) -> usize) -> Vec<(usize, usize)> {
+    (0..size).map(|i| (i, partner(i))).filter(|&(i, j)| i < j).collect()
+}
+
+/// The comparators of a sorting network, for drawing it, and how many of its layers have been run.
+#[derive(Clone)]
+pub struct Comparators {
+    pub layers: Arc<[Vec<(usize, usize)>]>,
+    pub done: usize,
+}
+
+/// A sorting network, which makes the same comparisons whatever the input. They're laid out in
+/// layers that never compare an element twice, so a whole layer is run in each step, as it would
+/// be on parallel hardware.
+pub struct NetworkSort {
+    network: Network,
+    layers: Arc<[Vec<(usize, usize)>]>,
+    done: usize,
+    started: bool,
+}
+
+impl NetworkSort {
+    pub const PARAMS: [Param; 1] = [Param {
+        name: "Network",
+        kind: Kind::Choice(&["bitonic", "odd-even merge", "odd-even transposition"]),
+        default: Value::Choice(0),
+    }];
+
+    pub fn new(network: Network) -> NetworkSort {
+        NetworkSort {
+            network,
+            layers: Arc::new([]),
+            done: 0,
+            started: false,
+        }
+    }
+}
+
+impl<T> Sort<T> for NetworkSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.layers = self.network.layers(a.len()).into();
+            self.started = true;
+        }
+
+        let Some(layer) = self.layers.get(self.done) else {
+            return SortResult::Done;
+        };
+        for &(i, j) in layer {
+            exchange(a, i, j, events);
+        }
+        self.done += 1;
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if !self.started {
+            return;
+        }
+        annotations.network = Some(Comparators { layers: self.layers.clone(), done: self.done });
+        annotations.stats.push(("layer", format!("{} of {}", self.done, self.layers.len())));
+    }
+}
@@ -0,0 +1,83 @@
+use super::*;
+
+enum State {
+    Find { i: usize, max: usize }, // looking for the largest unsorted element, up to `i`
+    FlipUp { max: usize }, // flipping the largest to the front
+    FlipDown, // flipping the front to the end of the unsorted part
+}
+
+/// Pancake sort, which can only reverse prefixes of the array, like flipping the top of a stack of
+/// pancakes with a spatula. The largest unsorted element is flipped to the front, then the whole
+/// unsorted part is flipped to put it at the end, taking at most 2n flips in all.
+pub struct PancakeSort {
+    size: usize, // of the unsorted part; everything after is sorted
+    state: State,
+    flips: usize,
+    started: bool,
+}
+
+impl PancakeSort {
+    pub fn new() -> PancakeSort {
+        PancakeSort {
+            size: 0,
+            state: State::Find { i: 1, max: 0 },
+            flips: 0,
+            started: false,
+        }
+    }
+
+    fn next_size(&mut self) {
+        self.size -= 1;
+        self.state = State::Find { i: 1, max: 0 };
+    }
+
+    fn flip<T>(&mut self, a: &mut [T], len: usize, events: &mut dyn Events) {
+        reverse(a, 0..len, events);
+        self.flips += 1;
+    }
+}
+
+impl<T> Sort<T> for PancakeSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.size = a.len();
+            self.started = true;
+        }
+        if self.size < 2 {
+            return SortResult::Done;
+        }
+
+        match self.state {
+            State::Find { i, max } if i < self.size => {
+                events.compare(max, i);
+                let max = if a[i] > a[max] { i } else { max };
+                self.state = State::Find { i: i + 1, max };
+            }
+            // already at the end, so no flips are needed
+            State::Find { max, .. } if max == self.size - 1 => self.next_size(),
+            State::Find { max: 0, .. } => self.state = State::FlipDown,
+            State::Find { max, .. } => self.state = State::FlipUp { max },
+            State::FlipUp { max } => {
+                self.flip(a, max + 1, events);
+                self.state = State::FlipDown;
+            }
+            State::FlipDown => {
+                self.flip(a, self.size, events);
+                self.next_size();
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if !self.started || self.size < 2 {
+            return;
+        }
+        annotations.ranges.push(0..self.size);
+        match self.state {
+            State::Find { max, .. } | State::FlipUp { max } => annotations.pivots.push(max),
+            State::FlipDown => annotations.pivots.push(0),
+        }
+        annotations.stats.push(("flips", self.flips.to_string()));
+    }
+}
@@ -0,0 +1,248 @@
+use std::mem;
+
+use rand::rngs::StdRng;
+
+use super::{quicksort::{stack_depth, Partition, Rules}, *};
+
+// the most elements a partial insertion sort may move before giving up on a range
+const PARTIAL_INSERTION_LIMIT: usize = 8;
+
+/// Pattern-defeating quicksort, after Orson Peters' pdqsort, which Rust's unstable sort was based
+/// on. On top of introsort it:
+///
+/// - tries a partial insertion sort on both halves of a range that was already partitioned, which
+///   sorts nearly sorted input in linear time
+/// - swaps a few elements around in the halves of a badly unbalanced partition, to break up
+///   whatever pattern made the pivot bad
+/// - heapsorts a range once log₂ n bad partitions have led to it
+///
+/// Unlike the original it doesn't pick the pivot as a ninther for long ranges, or partition
+/// elements equal to the pivot separately.
+pub struct PdqSort {
+    rules: Rules,
+    stack: Vec<Job>,
+    task: Option<Task>,
+    bad: usize, // partitions that left less than an eighth of the range on one side
+    heapsorted: usize,
+    started: bool,
+}
+
+struct Job {
+    range: Range<usize>,
+    bad_allowed: u32, // bad partitions still allowed before falling back to heapsort
+}
+
+enum Task {
+    Partition(Partition, u32), // with the bad partitions allowed
+    PartialInsertion {
+        ranges: [Range<usize>; 2], // the second is only tried once the first is sorted
+        i: usize,
+        j: usize,
+        moves: usize,
+        bad_allowed: u32,
+    },
+    BreakPatterns {
+        ranges: [Range<usize>; 2],
+        swaps: [(usize, usize); 4],
+        k: usize,
+        bad_allowed: u32,
+    },
+    Heap(HeapSort),
+}
+
+impl PdqSort {
+    pub const PARAMS: [Param; 2] = [
+        Param {
+            name: "Pivot",
+            kind: Kind::Choice(&["first", "last", "middle", "random", "median of three"]),
+            default: Value::Choice(4),
+        },
+        Param {
+            name: "Insertion cutoff",
+            kind: Kind::Int { min: 0, max: 32 },
+            default: Value::Int(24),
+        },
+    ];
+
+    pub fn new(pivot: PivotRule, cutoff: usize, rng: StdRng) -> PdqSort {
+        PdqSort {
+            rules: Rules::new(Scheme::Hoare, pivot, cutoff, rng),
+            stack: Vec::new(),
+            task: None,
+            bad: 0,
+            heapsorted: 0,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, len: usize) {
+        self.stack.reserve(stack_depth(len));
+        let bad_allowed = len.checked_ilog2().unwrap_or(0);
+        self.push([0..0, 0..len], bad_allowed);
+        self.started = true;
+    }
+
+    /// Pushes both halves of a partition, smaller last.
+    fn push(&mut self, [left, right]: [Range<usize>; 2], bad_allowed: u32) {
+        let (smaller, larger) = if left.len() < right.len() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        for range in [larger, smaller] {
+            if range.len() > 1 {
+                self.stack.push(Job { range, bad_allowed });
+            }
+        }
+    }
+
+    /// Decides what to do with the halves of a finished partition of `range`.
+    fn partitioned(
+        &mut self,
+        range: Range<usize>,
+        halves: [Range<usize>; 2],
+        moved: bool,
+        bad_allowed: u32,
+    ) {
+        let [left, right] = &halves;
+        if left.len() < range.len() / 8 || right.len() < range.len() / 8 {
+            self.bad += 1;
+            if bad_allowed <= 1 {
+                self.heapsorted += 1;
+                self.task = Some(Task::Heap(HeapSort::over(range)));
+            } else {
+                // swaps a quarter of the way into each half with its end
+                let swaps = [
+                    (left.start, left.start + left.len() / 4),
+                    (left.end.saturating_sub(1), left.end.saturating_sub(left.len() / 4 + 1)),
+                    (right.start, right.start + right.len() / 4),
+                    (right.end.saturating_sub(1), right.end.saturating_sub(right.len() / 4 + 1)),
+                ];
+                self.task = Some(Task::BreakPatterns {
+                    ranges: halves,
+                    swaps,
+                    k: 0,
+                    bad_allowed: bad_allowed - 1,
+                });
+            }
+        } else if !moved {
+            // nothing had to be moved, so the range may well be sorted already
+            self.task = Some(Task::PartialInsertion {
+                i: left.start,
+                j: left.start,
+                ranges: halves,
+                moves: 0,
+                bad_allowed,
+            });
+        } else {
+            self.task = None;
+            self.push(halves, bad_allowed);
+        }
+    }
+}
+
+impl<T> Sort<T> for PdqSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a.len());
+        }
+
+        let task = match &mut self.task {
+            Some(task) => task,
+            None => match self.stack.pop() {
+                Some(Job { range, bad_allowed }) => {
+                    let partition = Partition::new(range, &self.rules);
+                    self.task.insert(Task::Partition(partition, bad_allowed))
+                }
+                None => return SortResult::Done,
+            },
+        };
+
+        match task {
+            Task::Partition(partition, bad_allowed) => {
+                if let Some((left, right)) = partition.step(a, &mut self.rules, events) {
+                    if partition.method() == Method::Insertion {
+                        self.task = None;
+                    } else {
+                        let range = partition.range.clone();
+                        let (moved, bad_allowed) = (partition.moved, *bad_allowed);
+                        self.partitioned(range, [left, right], moved, bad_allowed);
+                    }
+                }
+            }
+            Task::PartialInsertion { ranges, i, j, moves, bad_allowed } => {
+                let range = ranges[0].clone();
+                if *i >= range.end {
+                    // sorted without going over the limit, so on to the other half
+                    if ranges[1].is_empty() {
+                        self.task = None;
+                    } else {
+                        ranges[0] = mem::replace(&mut ranges[1], 0..0);
+                        (*i, *j, *moves) = (ranges[0].start, ranges[0].start, 0);
+                    }
+                } else if *moves > PARTIAL_INSERTION_LIMIT {
+                    // too far from sorted, so both halves are partitioned after all
+                    let (ranges, bad_allowed) = (ranges.clone(), *bad_allowed);
+                    self.task = None;
+                    self.push(ranges, bad_allowed);
+                } else {
+                    if *j > range.start {
+                        events.compare(*j - 1, *j);
+                    }
+                    if *j > range.start && a[*j - 1] > a[*j] {
+                        swap(a, *j, *j - 1);
+                        events.swap(*j, *j - 1);
+                        *j -= 1;
+                        *moves += 1;
+                    } else {
+                        *i += 1;
+                        *j = *i;
+                    }
+                }
+            }
+            Task::BreakPatterns { ranges, swaps, k, bad_allowed } => {
+                // halves too short to have a pattern worth breaking are left alone
+                let long = |x: usize| ranges.iter().any(|r| r.contains(&x) && r.len() >= 8);
+                match swaps.get(*k) {
+                    Some(&(x, y)) if x != y && long(x) => {
+                        swap(a, x, y);
+                        events.swap(x, y);
+                        *k += 1;
+                    }
+                    Some(_) => *k += 1,
+                    None => {
+                        let (ranges, bad_allowed) = (ranges.clone(), *bad_allowed);
+                        self.task = None;
+                        self.push(ranges, bad_allowed);
+                    }
+                }
+            }
+            Task::Heap(heap) => {
+                if let SortResult::Done = heap.step(a, events) {
+                    self.task = None;
+                }
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let (range, method) = match &self.task {
+            Some(Task::Partition(partition, _)) => {
+                annotations.pivots.extend(partition.pivot());
+                (partition.range.clone(), partition.method())
+            }
+            Some(Task::PartialInsertion { ranges, .. }) => (ranges[0].clone(), Method::Insertion),
+            Some(Task::BreakPatterns { ranges, .. }) => {
+                let [left, right] = ranges;
+                (left.start.min(right.start)..left.end.max(right.end), Method::Partition)
+            }
+            Some(Task::Heap(heap)) => (heap.heap(), Method::Heap),
+            None => return,
+        };
+        annotations.ranges.push(range.clone());
+        annotations.methods.push((range, method));
+        annotations.stats.push(("bad partitions", self.bad.to_string()));
+        annotations.stats.push(("heapsorted ranges", self.heapsorted.to_string()));
+    }
+}
@@ -1,111 +1,399 @@
+use rand::rngs::StdRng;
+
 use super::*;
 
-struct Partition<T> where T: PartialOrd + Clone {
-    start: usize,
-    end: usize,
-    sort: QuickSort<T>,
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scheme {
+    Lomuto,
+    Hoare,
+    ThreeWay, // Dijkstra's Dutch national flag partitioning, which groups elements equal to the pivot
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 3] = [Scheme::Lomuto, Scheme::Hoare, Scheme::ThreeWay];
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PivotRule {
+    First,
+    Last,
+    Middle,
+    Random,
+    MedianOfThree,
 }
 
-impl<T> Partition<T> where T: PartialOrd + Clone {
-    fn new(start: usize, end: usize) -> Partition<T> {
-        Partition {
-            start,
-            end,
-            sort: QuickSort::new(),
+impl PivotRule {
+    pub const ALL: [PivotRule; 5] = [
+        PivotRule::First,
+        PivotRule::Last,
+        PivotRule::Middle,
+        PivotRule::Random,
+        PivotRule::MedianOfThree,
+    ];
+}
+
+/// How ranges are partitioned, shared by all the partitions of a sort.
+pub(super) struct Rules {
+    scheme: Scheme,
+    pivot: PivotRule,
+    pub(super) cutoff: usize, // ranges this short are insertion sorted instead
+    rng: StdRng,
+}
+
+impl Rules {
+    pub(super) fn new(scheme: Scheme, pivot: PivotRule, cutoff: usize, rng: StdRng) -> Rules {
+        Rules {
+            scheme,
+            pivot,
+            cutoff,
+            rng,
         }
     }
 
-    fn step(&mut self, a: &mut [T]) -> SortResult {
-        self.sort.step(a)
+    fn choose_pivot<T>(&mut self, a: &[T], range: Range<usize>, events: &mut dyn Events) -> usize
+    where T: PartialOrd {
+        let (first, middle, last) = (range.start, range.start + range.len() / 2, range.end - 1);
+        match self.pivot {
+            PivotRule::First => first,
+            PivotRule::Last => last,
+            PivotRule::Middle => middle,
+            PivotRule::Random => self.rng.gen_range(range),
+            PivotRule::MedianOfThree => {
+                events.compare(first, middle);
+                let (low, high) = if a[middle] < a[first] { (middle, first) } else { (first, middle) };
+                events.compare(last, low);
+                if a[last] < a[low] {
+                    return low;
+                }
+                events.compare(last, high);
+                if a[last] > a[high] { high } else { last }
+            }
+        }
     }
 }
 
-pub struct QuickSort<T> where T: PartialOrd + Clone {
-    partitions: Option<(Rc<RefCell<Partition<T>>>, Rc<RefCell<Partition<T>>>)>,
-    pivot: Option<T>,
-    i: usize,
-    j: usize,
+enum State {
+    Choosing, // the first step picks the pivot and moves it to where the scheme keeps it
+    Lomuto { i: usize, j: usize }, // pivot at the end
+    Hoare { i: usize, j: usize, scan: Scan }, // pivot at the start
+    ThreeWay { lt: usize, i: usize, gt: usize }, // pivot value at `lt`
+    Insertion { i: usize, j: usize }, // short ranges are insertion sorted rather than partitioned
 }
 
-impl<T> QuickSort<T> where T: PartialOrd + Clone {
-    pub fn new() -> QuickSort<T> {
-        QuickSort {
-            partitions: None,
-            pivot: None,
-            i: 0,
-            j: 0,
-        }
-    }
+#[derive(Clone, Copy)]
+enum Scan {
+    Left, // moving `i` right past elements smaller than the pivot
+    Right, // moving `j` left past elements larger than the pivot
+    Swap, // both have stopped
+}
 
-    fn create_partitions(&mut self, a: &mut [T]) -> SortResult {
-        let i = self.i;
-        let j = a.len() - 1;
-        swap(a, i, j);
+/// A partition of one range, one comparison per step.
+pub(super) struct Partition {
+    pub(super) range: Range<usize>,
+    state: State,
+    pub(super) moved: bool, // whether any element other than the pivot has been swapped
+}
 
-        self.partitions = Some((
-            Rc::new(RefCell::new(Partition::new(0, i))),
-            Rc::new(RefCell::new(Partition::new(i + 1, a.len()))),
-        ));
+impl Partition {
+    pub(super) fn new(range: Range<usize>, rules: &Rules) -> Partition {
+        let state = if range.len() <= rules.cutoff {
+            State::Insertion { i: range.start + 1, j: range.start + 1 }
+        } else {
+            State::Choosing
+        };
+        Partition { range, state, moved: false }
+    }
 
-        SortResult::Swap(vec![self.i, a.len() - 1])
+    pub(super) fn pivot(&self) -> Option<usize> {
+        match self.state {
+            State::Choosing | State::Insertion { .. } => None,
+            State::Lomuto { .. } => Some(self.range.end - 1),
+            State::Hoare { .. } => Some(self.range.start),
+            State::ThreeWay { lt, .. } => Some(lt),
+        }
     }
 
-    fn sort_partitions(
+    pub(super) fn method(&self) -> Method {
+        match self.state {
+            State::Insertion { .. } => Method::Insertion,
+            _ => Method::Partition,
+        }
+    }
+
+    /// Returns the two ranges left on either side of the pivot once the partition is finished, or
+    /// a pair of empty ones after an insertion sort.
+    pub(super) fn step<T>(
+        &mut self,
         a: &mut [T],
-        p1: &mut Rc<RefCell<Partition<T>>>,
-        p2: &mut Rc<RefCell<Partition<T>>>
-    ) -> SortResult {
-        let (p1_start, p1_end) = (p1.borrow().start, p1.borrow().end);
-        let (p2_start, p2_end) = (p2.borrow().start, p2.borrow().end);
-        match (
-            p1.borrow_mut().step(&mut a[p1_start..p1_end]),
-            p2.borrow_mut().step(&mut a[p2_start..p2_end])
-        ) {
-            (SortResult::Done, SortResult::Done) => SortResult::Done,
-            (SortResult::Swap(mut a), SortResult::Swap(mut b)) => {
-                a.append(&mut b);
-                SortResult::Swap(a)
+        rules: &mut Rules,
+        events: &mut dyn Events,
+    ) -> Option<(Range<usize>, Range<usize>)> where T: PartialOrd + Clone {
+        let (start, end) = (self.range.start, self.range.end);
+
+        match &mut self.state {
+            State::Choosing => {
+                let pivot = rules.choose_pivot(a, start..end, events);
+                let home = if rules.scheme == Scheme::Lomuto { end - 1 } else { start };
+                if pivot != home {
+                    swap(a, pivot, home);
+                    events.swap(pivot, home);
+                }
+                self.state = match rules.scheme {
+                    Scheme::Lomuto => State::Lomuto { i: start, j: start },
+                    Scheme::Hoare => State::Hoare { i: start + 1, j: end - 1, scan: Scan::Left },
+                    Scheme::ThreeWay => State::ThreeWay { lt: start, i: start + 1, gt: end - 1 },
+                };
+                None
+            }
+            State::Lomuto { i, j } => {
+                let pivot = end - 1;
+                if *j < pivot {
+                    events.compare(*j, pivot);
+                    if a[*j] <= a[pivot] {
+                        if *i != *j {
+                            swap(a, *i, *j);
+                            events.swap(*i, *j);
+                            self.moved = true;
+                        }
+                        *i += 1;
+                    }
+                    *j += 1;
+                    None
+                } else {
+                    swap(a, *i, pivot);
+                    events.swap(*i, pivot);
+                    Some((start..*i, *i + 1..end))
+                }
+            }
+            State::Hoare { i, j, scan } => loop {
+                match *scan {
+                    Scan::Left if *i <= *j => {
+                        events.compare(*i, start);
+                        if a[*i] < a[start] {
+                            *i += 1;
+                        } else {
+                            *scan = Scan::Right;
+                        }
+                        return None;
+                    }
+                    Scan::Left => *scan = Scan::Right,
+                    Scan::Right if *j >= *i => {
+                        events.compare(*j, start);
+                        if a[*j] > a[start] {
+                            *j -= 1;
+                        } else {
+                            *scan = Scan::Swap;
+                        }
+                        return None;
+                    }
+                    Scan::Right | Scan::Swap if *i < *j => {
+                        swap(a, *i, *j);
+                        events.swap(*i, *j);
+                        self.moved = true;
+                        *i += 1;
+                        *j -= 1;
+                        *scan = Scan::Left;
+                        return None;
+                    }
+                    // the scans have crossed, so `j` is the last element that isn't larger than
+                    // the pivot
+                    Scan::Right | Scan::Swap => {
+                        swap(a, start, *j);
+                        events.swap(start, *j);
+                        return Some((start..*j, *j + 1..end));
+                    }
+                }
+            },
+            State::ThreeWay { lt, i, gt } => {
+                if *i <= *gt {
+                    events.compare(*i, *lt);
+                    if a[*i] < a[*lt] {
+                        swap(a, *lt, *i);
+                        events.swap(*lt, *i);
+                        self.moved = true;
+                        *lt += 1;
+                        *i += 1;
+                    } else if a[*i] > a[*lt] {
+                        swap(a, *i, *gt);
+                        events.swap(*i, *gt);
+                        self.moved = true;
+                        *gt -= 1;
+                    } else {
+                        *i += 1;
+                    }
+                    None
+                } else {
+                    Some((start..*lt, *gt + 1..end))
+                }
+            }
+            State::Insertion { i, j } => {
+                if *i < end {
+                    if *j > start {
+                        events.compare(*j - 1, *j);
+                    }
+                    if *j > start && a[*j - 1] > a[*j] {
+                        swap(a, *j, *j - 1);
+                        events.swap(*j, *j - 1);
+                        *j -= 1;
+                    } else {
+                        *i += 1;
+                        *j = *i;
+                    }
+                    None
+                } else {
+                    Some((start..start, end..end))
+                }
             }
-            (SortResult::Swap(a), _) | (_, SortResult::Swap(a)) => SortResult::Swap(a),
-            _ => SortResult::Ok,
         }
     }
+}
 
-    fn sort_pivot(&mut self, a: &mut [T]) -> SortResult {
-        let Some(pivot) = &self.pivot else {
-            panic!() // pivot should never be None in this case
-        };
-        if a[self.j] <= *pivot {
-            let i = self.i;
-            let j = self.j;
-            swap(a, i, j);
-            self.i += 1;
-            self.j += 1;
-            SortResult::Swap(vec![i, j])
-        } else {
-            self.j += 1;
-            SortResult::Ok
+/// The most ranges waiting on the stack while sorting `len` elements, given that the smaller half
+/// of each split is always sorted first: every range pushed on top of another is at most half its
+/// size, so there's at most one per halving of the array.
+pub(super) fn stack_depth(len: usize) -> usize {
+    usize::BITS as usize - len.leading_zeros() as usize + 1
+}
+
+/// Partitions one range at a time, the way quicksort actually runs. The ranges still waiting to be
+/// partitioned are kept on an explicit stack instead of being recursed into.
+pub struct QuickSort {
+    rules: Rules,
+    stack: Vec<Range<usize>>,
+    partition: Option<Partition>,
+    started: bool,
+}
+
+impl QuickSort {
+    // shared with `ParallelQuickSort`
+    pub const PARAMS: [Param; 3] = [
+        Param {
+            name: "Partition scheme",
+            kind: Kind::Choice(&["Lomuto", "Hoare", "three-way"]),
+            default: Value::Choice(0),
+        },
+        Param {
+            name: "Pivot",
+            kind: Kind::Choice(&["first", "last", "middle", "random", "median of three"]),
+            default: Value::Choice(1),
+        },
+        Param {
+            name: "Insertion cutoff",
+            kind: Kind::Int { min: 0, max: 32 },
+            default: Value::Int(0),
+        },
+    ];
+
+    pub fn new(scheme: Scheme, pivot: PivotRule, cutoff: usize, rng: StdRng) -> QuickSort {
+        QuickSort {
+            rules: Rules::new(scheme, pivot, cutoff, rng),
+            stack: Vec::new(),
+            partition: None,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, len: usize) {
+        self.stack.reserve(stack_depth(len));
+        self.push(0..len);
+        self.started = true;
+    }
+
+    fn push(&mut self, range: Range<usize>) {
+        if range.len() > 1 {
+            self.stack.push(range);
         }
     }
 }
 
-impl<T> Sort<T> for QuickSort<T> where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
-        if a.len() <= 1 {
-            SortResult::Done
-        } else {
-            if let Some((p1, p2)) = &mut self.partitions {
-                QuickSort::sort_partitions(a, p1, p2)
-            } else if self.pivot == None {
-                self.pivot = Some(a[a.len() - 1].clone());
-                SortResult::Ok
+impl<T> Sort<T> for QuickSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a.len());
+        }
+
+        let partition = match &mut self.partition {
+            Some(partition) => partition,
+            None => match self.stack.pop() {
+                Some(range) => self.partition.insert(Partition::new(range, &self.rules)),
+                None => return SortResult::Done,
+            },
+        };
+
+        if let Some((left, right)) = partition.step(a, &mut self.rules, events) {
+            self.partition = None;
+            let (smaller, larger) = if left.len() < right.len() {
+                (left, right)
             } else {
-                if self.j < a.len() - 1 {
-                    self.sort_pivot(a)
-                } else {
-                    self.create_partitions(a)
+                (right, left)
+            };
+            self.push(larger);
+            self.push(smaller);
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if let Some(partition) = &self.partition {
+            annotations.pivots.extend(partition.pivot());
+            annotations.ranges.push(partition.range.clone());
+        }
+    }
+}
+
+/// Partitions every pending range at once, advancing each of them by one comparison per step. Not
+/// how quicksort runs on a single core, but it shows the halves being split independently.
+pub struct ParallelQuickSort {
+    rules: Rules,
+    partitions: Vec<Partition>,
+    started: bool,
+}
+
+impl ParallelQuickSort {
+    pub fn new(scheme: Scheme, pivot: PivotRule, cutoff: usize, rng: StdRng) -> ParallelQuickSort {
+        ParallelQuickSort {
+            rules: Rules::new(scheme, pivot, cutoff, rng),
+            partitions: Vec::new(),
+            started: false,
+        }
+    }
+}
+
+impl<T> Sort<T> for ParallelQuickSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            // partitions are disjoint and hold at least two elements each
+            self.partitions.reserve(a.len() / 2 + 1);
+            if a.len() > 1 {
+                self.partitions.push(Partition::new(0..a.len(), &self.rules));
+            }
+            self.started = true;
+        }
+
+        if self.partitions.is_empty() {
+            return SortResult::Done;
+        }
+
+        // backwards, so that removing a finished partition only moves one that has already been
+        // stepped, or one that was split off during this step, into its place
+        for p in (0..self.partitions.len()).rev() {
+            let Some((left, right)) = self.partitions[p].step(a, &mut self.rules, events) else {
+                continue;
+            };
+            self.partitions.swap_remove(p);
+            for half in [left, right] {
+                if half.len() > 1 {
+                    self.partitions.push(Partition::new(half, &self.rules));
                 }
             }
         }
+        SortResult::Ok
     }
-}
\ No newline at end of file
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        for partition in &self.partitions {
+            annotations.pivots.extend(partition.pivot());
+            annotations.ranges.push(partition.range.clone());
+        }
+    }
+}
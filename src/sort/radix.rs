@@ -0,0 +1,175 @@
+use super::*;
+
+/// Radix sort, dropping each element into a bucket for its current digit and then writing the
+/// buckets back in order. LSD sorts the whole array by each digit in turn, starting with the least
+/// significant; MSD sorts by the most significant digit first, then sorts each bucket separately by
+/// the next digit.
+pub struct RadixSort {
+    msd: bool,
+    base: usize,
+    buckets: Vec<Vec<usize>>,
+    stack: Vec<Pass>, // MSD only: buckets still to be sorted by their next digit
+    pass: Option<Pass>,
+    digits: u32, // in the largest value
+    started: bool,
+}
+
+struct Pass {
+    range: Range<usize>,
+    digit: u32, // counting from the least significant, which is 0
+    phase: Phase,
+}
+
+enum Phase {
+    Scatter { i: usize }, // next element to drop into its bucket
+    Gather { bucket: usize, j: usize, k: usize }, // writing element `j` of `bucket` back to `k`
+}
+
+impl Pass {
+    fn new(range: Range<usize>, digit: u32) -> Pass {
+        Pass {
+            phase: Phase::Scatter { i: range.start },
+            range,
+            digit,
+        }
+    }
+}
+
+impl RadixSort {
+    pub const PARAMS: [Param; 1] = [
+        Param {
+            name: "Base",
+            kind: Kind::Int { min: 2, max: 64 },
+            default: Value::Int(10),
+        },
+    ];
+
+    pub fn lsd(base: usize) -> RadixSort {
+        RadixSort::new(false, base)
+    }
+
+    pub fn msd(base: usize) -> RadixSort {
+        RadixSort::new(true, base)
+    }
+
+    fn new(msd: bool, base: usize) -> RadixSort {
+        RadixSort {
+            msd,
+            base,
+            buckets: Vec::new(),
+            stack: Vec::new(),
+            pass: None,
+            digits: 1,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, a: &[usize]) {
+        let max = a.iter().copied().max().unwrap_or(0);
+        while self.base.checked_pow(self.digits).is_some_and(|place| max / place > 0) {
+            self.digits += 1;
+        }
+
+        // every bucket may end up holding the whole array, and MSD leaves at most `base - 1`
+        // buckets waiting at each digit
+        self.buckets = (0..self.base).map(|_| Vec::with_capacity(a.len())).collect();
+        if self.msd {
+            self.stack.reserve(self.digits as usize * self.base);
+        }
+
+        if a.len() > 1 {
+            let first = if self.msd { self.digits - 1 } else { 0 };
+            self.pass = Some(Pass::new(0..a.len(), first));
+        }
+        self.started = true;
+    }
+
+    /// Moves on once every bucket has been written back.
+    fn next_pass(&mut self, pass: Pass) {
+        if self.msd {
+            // each bucket is now a range of its own, sorted by the next digit down. They're
+            // pushed last first, so that they come off the stack in order.
+            let mut end = pass.range.end;
+            for bucket in self.buckets.iter().rev() {
+                let range = end - bucket.len()..end;
+                if range.len() > 1 && pass.digit > 0 {
+                    self.stack.push(Pass::new(range.clone(), pass.digit - 1));
+                }
+                end = range.start;
+            }
+            self.pass = self.stack.pop();
+        } else if pass.digit + 1 < self.digits {
+            self.pass = Some(Pass::new(pass.range, pass.digit + 1));
+        } else {
+            self.pass = None;
+        }
+
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+    }
+}
+
+impl Sort<usize> for RadixSort {
+    fn step(&mut self, a: &mut [usize], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a);
+        }
+
+        let Some(pass) = &mut self.pass else {
+            return SortResult::Done;
+        };
+        let divisor = self.base.pow(pass.digit);
+
+        loop {
+            match &mut pass.phase {
+                Phase::Scatter { i } if *i < pass.range.end => {
+                    events.read(*i);
+                    self.buckets[a[*i] / divisor % self.base].push(a[*i]);
+                    *i += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Scatter { .. } => {
+                    pass.phase = Phase::Gather { bucket: 0, j: 0, k: pass.range.start };
+                }
+                // skip past buckets that are empty or have been written back
+                Phase::Gather { bucket, j, .. }
+                    if *bucket < self.base && *j == self.buckets[*bucket].len() =>
+                {
+                    *bucket += 1;
+                    *j = 0;
+                }
+                Phase::Gather { bucket, j, k } if *bucket < self.base => {
+                    a[*k] = self.buckets[*bucket][*j];
+                    events.write(*k);
+                    *j += 1;
+                    *k += 1;
+                    return SortResult::Ok;
+                }
+                Phase::Gather { .. } => {
+                    let pass = self.pass.take().unwrap();
+                    self.next_pass(pass);
+                    return SortResult::Ok;
+                }
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let Some(pass) = &self.pass else {
+            return;
+        };
+
+        annotations.buckets = match pass.phase {
+            Phase::Scatter { .. } => self.buckets.clone(),
+            Phase::Gather { bucket, j, .. } => unwritten(&self.buckets, bucket, j),
+        };
+        if self.msd {
+            annotations.ranges.push(pass.range.clone());
+        }
+        annotations.stats.push((
+            "digit",
+            format!("{} of {} (×{})", pass.digit + 1, self.digits, self.base.pow(pass.digit)),
+        ));
+    }
+}
@@ -15,12 +15,13 @@ impl SelectionSort {
         }
     }
 
-    fn check<T>(&mut self, a: &mut [T]) -> SortResult where T: PartialOrd + Clone {
+    fn check<T>(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult where T: PartialOrd + Clone {
+        events.compare(self.j, self.min);
         if a[self.j] < a[self.min] {
             self.min = self.j;
         }
         self.j += 1;
-        SortResult::Swap(vec![self.j]) // makes this sort more visually interesting
+        SortResult::Ok
     }
 
     fn next_pass(&mut self) {
@@ -29,22 +30,23 @@ impl SelectionSort {
         self.min = self.i;
     }
 
-    fn swap_min<T>(&mut self, a: &mut [T]) -> SortResult where T: PartialOrd + Clone {
+    fn swap_min<T>(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult where T: PartialOrd + Clone {
         let i = self.i;
         let min = self.min;
         swap(a, i, min);
+        events.swap(i, min);
         self.next_pass();
-        SortResult::Swap(vec![i, min])
+        SortResult::Ok
     }
 }
 
 impl<T> Sort<T> for SelectionSort where T: PartialOrd + Clone {
-    fn step(&mut self, a: &mut [T]) -> SortResult {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
         if self.i < a.len() - 1 {
             if self.j < a.len() {
-                self.check(a)
+                self.check(a, events)
             } else if self.min != self.i {
-                self.swap_min(a)
+                self.swap_min(a, events)
             } else {
                 self.next_pass();
                 SortResult::Ok
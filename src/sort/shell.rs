@@ -0,0 +1,113 @@
+use std::iter;
+
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GapSequence {
+    Shell, // n/2, n/4, ..., 1
+    Knuth, // (3^k - 1) / 2, up to n/3
+    Sedgewick, // 4^k + 3 * 2^(k-1) + 1
+    Ciura, // found experimentally, extended by a factor of 2.25
+    Tokuda, // ceil((9^k - 4^k) / (5 * 4^(k-1)))
+}
+
+impl GapSequence {
+    pub const ALL: [GapSequence; 5] = [
+        GapSequence::Shell,
+        GapSequence::Knuth,
+        GapSequence::Sedgewick,
+        GapSequence::Ciura,
+        GapSequence::Tokuda,
+    ];
+
+    /// The gaps to sort `len` elements with, smallest first.
+    pub fn gaps(self, len: usize) -> Vec<usize> {
+        let mut gaps: Vec<usize> = match self {
+            GapSequence::Shell => {
+                let halves = iter::successors(Some(len / 2), |&gap| Some(gap / 2));
+                let mut gaps: Vec<_> = halves.take_while(|&gap| gap > 0).collect();
+                gaps.reverse();
+                gaps
+            }
+            GapSequence::Knuth => iter::successors(Some(1), |&gap| Some(gap * 3 + 1))
+                .take_while(|&gap| gap == 1 || gap <= len / 3)
+                .collect(),
+            GapSequence::Sedgewick => iter::once(1)
+                .chain((1..).map(|k| 4usize.pow(k) + 3 * 2usize.pow(k - 1) + 1))
+                .take_while(|&gap| gap < len)
+                .collect(),
+            GapSequence::Ciura => iter::successors(Some(1), |&gap| {
+                // past the end of the published sequence, each gap is 2.25 times the last
+                Some(CIURA.into_iter().find(|&c| c > gap).unwrap_or((gap as f64 * 2.25) as usize))
+            })
+                .take_while(|&gap| gap < len)
+                .collect(),
+            GapSequence::Tokuda => (1..)
+                .map(|k| (0.8 * (2.25f64.powi(k) - 1.0)).ceil() as usize)
+                .take_while(|&gap| gap < len)
+                .collect(),
+        };
+
+        // arrays too short for any gap still get a pass, which finds them already sorted
+        if gaps.is_empty() {
+            gaps.push(1);
+        }
+        gaps
+    }
+}
+
+const CIURA: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+
+/// Insertion sorts the elements `gap` apart for each of a shrinking sequence of gaps, ending with an
+/// ordinary insertion sort once the array is nearly sorted.
+pub struct ShellSort {
+    sequence: GapSequence,
+    gaps: Vec<usize>, // still to come, smallest first
+    pass: Option<InsertionSort>,
+}
+
+impl ShellSort {
+    pub const PARAMS: [Param; 1] = [
+        Param {
+            name: "Gap sequence",
+            kind: Kind::Choice(&["Shell", "Knuth", "Sedgewick", "Ciura", "Tokuda"]),
+            default: Value::Choice(3),
+        },
+    ];
+
+    pub fn new(sequence: GapSequence) -> ShellSort {
+        ShellSort {
+            sequence,
+            gaps: Vec::new(),
+            pass: None,
+        }
+    }
+}
+
+impl<T> Sort<T> for ShellSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        let pass = match &mut self.pass {
+            Some(pass) => pass,
+            None => {
+                self.gaps = self.sequence.gaps(a.len());
+                self.pass.insert(InsertionSort::with_gap(self.gaps.pop().unwrap_or(1)))
+            }
+        };
+
+        if let SortResult::Done = pass.step(a, events) {
+            match self.gaps.pop() {
+                Some(gap) => *pass = InsertionSort::with_gap(gap),
+                None => return SortResult::Done,
+            }
+        }
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if let Some(pass) = &self.pass {
+            let (first, gap) = pass.chain();
+            annotations.chain = Some((first, gap));
+            annotations.stats.push(("gap", gap.to_string()));
+        }
+    }
+}
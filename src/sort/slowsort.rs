@@ -0,0 +1,75 @@
+use super::*;
+
+enum Call {
+    Sort(Range<usize>),
+    // swapping the largest of the first half of `range`, at `mid`, with its last element if
+    // that's smaller, which leaves the largest element of the range at the end
+    Fix { range: Range<usize>, mid: usize },
+}
+
+/// Broder and Stolfi's slowsort, which "multiplies and surrenders": it finds the largest element
+/// by sorting both halves and comparing their largest, moves it to the end, and then sorts
+/// everything else. It does a lot of recursing for each comparison, so it takes
+/// n^(log n / (2 + ε)) steps, more than any polynomial.
+pub struct SlowSort {
+    stack: Vec<Call>, // calls still to make, the next one last
+    current: Option<(Range<usize>, usize)>,
+    started: bool,
+}
+
+impl SlowSort {
+    pub fn new() -> SlowSort {
+        SlowSort {
+            stack: Vec::new(),
+            current: None,
+            started: false,
+        }
+    }
+}
+
+impl<T> Sort<T> for SlowSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            // sorting the first half leaves three calls waiting, and sorting the rest of the
+            // range replaces the call that made it, so the stack only grows with the halving
+            let bits = (usize::BITS - a.len().leading_zeros()) as usize;
+            self.stack.reserve(3 * bits + 4);
+            self.stack.push(Call::Sort(0..a.len()));
+            self.started = true;
+        }
+
+        // sorting is all calls until there's something to compare
+        loop {
+            match self.stack.pop() {
+                Some(Call::Sort(range)) if range.len() >= 2 => {
+                    let (start, end) = (range.start, range.end);
+                    let mid = (start + end - 1) / 2;
+                    self.stack.extend([
+                        Call::Sort(start..end - 1),
+                        Call::Fix { range, mid },
+                        Call::Sort(mid + 1..end),
+                        Call::Sort(start..mid + 1),
+                    ]);
+                }
+                Some(Call::Sort(_)) => (),
+                Some(Call::Fix { range, mid }) => {
+                    exchange(a, mid, range.end - 1, events);
+                    self.current = Some((range, mid));
+                    return SortResult::Ok;
+                }
+                None => {
+                    self.current = None;
+                    return SortResult::Done;
+                }
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if let Some((range, mid)) = &self.current {
+            annotations.ranges.push(range.clone());
+            annotations.pivots.push(*mid);
+        }
+        annotations.stats.push(("calls waiting", self.stack.len().to_string()));
+    }
+}
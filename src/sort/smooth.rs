@@ -0,0 +1,176 @@
+use super::*;
+
+// Leonardo numbers, L(k) = L(k - 1) + L(k - 2) + 1, which are the sizes of the heaps
+const LEONARDO: [usize; 64] = {
+    let mut leonardo = [1usize; 64];
+    let mut k = 2;
+    while k < 64 {
+        leonardo[k] = leonardo[k - 1].saturating_add(leonardo[k - 2]).saturating_add(1);
+        k += 1;
+    }
+    leonardo
+};
+
+enum Fix {
+    // moving the root of heap `heap` left past the roots of the heaps before it that are larger
+    Trinkle { heap: usize },
+    Sift { node: usize, order: usize }, // down a heap of this order, rooted at `node`
+}
+
+/// Dijkstra's smoothsort, a heapsort that keeps the array as a forest of Leonardo heaps rather than
+/// a single binary heap. Each heap of order k has the root at its end, with heaps of order k - 1
+/// and k - 2 before it as children, and the roots of the heaps increase from left to right. As
+/// the largest element is always at the end of the forest, it can be taken off without moving it.
+/// On sorted input nothing ever needs to move, so it approaches linear time the closer to sorted
+/// the array is.
+pub struct SmoothSort {
+    orders: Vec<usize>, // of the heaps in the forest, left to right
+    next: usize, // while building, elements from here on aren't in the forest yet
+    building: bool,
+    fix: Option<Fix>,
+    pending: Option<usize>, // another heap whose root needs trinkling once this fix is done
+    len: usize,
+    started: bool,
+}
+
+impl SmoothSort {
+    pub fn new() -> SmoothSort {
+        SmoothSort {
+            orders: Vec::new(),
+            next: 0,
+            building: true,
+            fix: None,
+            pending: None,
+            len: 0,
+            started: false,
+        }
+    }
+
+    /// The elements of each heap in the forest, left to right.
+    fn heaps(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.orders.iter().scan(0, |start, &order| {
+            let heap = *start..*start + LEONARDO[order];
+            *start = heap.end;
+            Some(heap)
+        })
+    }
+
+    fn root(&self, heap: usize) -> usize {
+        self.orders[..=heap].iter().map(|&order| LEONARDO[order]).sum::<usize>() - 1
+    }
+
+    /// Adds the next element to the forest, as the root of a new heap merging the last two if
+    /// they're of consecutive orders, or else as a heap of its own.
+    fn add(&mut self) {
+        match *self.orders.as_slice() {
+            [.., left, right] if left == right + 1 => {
+                self.orders.pop();
+                *self.orders.last_mut().unwrap() += 1;
+            }
+            [.., 1] => self.orders.push(0),
+            _ => self.orders.push(1),
+        }
+        self.next += 1;
+    }
+
+    /// The children of the root of a heap of `order`, left first, with the orders of their heaps.
+    fn children(node: usize, order: usize) -> [(usize, usize); 2] {
+        [(node - 1 - LEONARDO[order - 2], order - 1), (node - 1, order - 2)]
+    }
+}
+
+impl<T> Sort<T> for SmoothSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.len = a.len();
+            // there are never more heaps than orders that fit in the array
+            self.orders.reserve(LEONARDO.iter().take_while(|&&l| l <= a.len()).count() + 1);
+            self.started = true;
+        }
+
+        loop {
+            match self.fix {
+                Some(Fix::Trinkle { heap }) => {
+                    let (root, order) = (self.root(heap), self.orders[heap]);
+                    let sift = Fix::Sift { node: root, order };
+                    if heap == 0 {
+                        self.fix = Some(sift);
+                        continue;
+                    }
+
+                    // the previous root moves right if it's larger than this root and its
+                    // children, which the previous root would otherwise end up above
+                    let previous = root - LEONARDO[order];
+                    events.compare(previous, root);
+                    let mut larger = a[previous] > a[root];
+                    if larger && order >= 2 {
+                        for (child, _) in SmoothSort::children(root, order) {
+                            events.compare(previous, child);
+                            larger &= a[previous] > a[child];
+                        }
+                    }
+                    if larger {
+                        swap(a, previous, root);
+                        events.swap(previous, root);
+                        self.fix = Some(Fix::Trinkle { heap: heap - 1 });
+                    } else {
+                        self.fix = Some(sift);
+                    }
+                    return SortResult::Ok;
+                }
+                Some(Fix::Sift { node, order }) if order >= 2 => {
+                    let [left, right] = SmoothSort::children(node, order);
+                    events.compare(left.0, right.0);
+                    let (child, child_order) = if a[left.0] < a[right.0] { right } else { left };
+                    events.compare(node, child);
+                    if a[node] < a[child] {
+                        swap(a, node, child);
+                        events.swap(node, child);
+                        self.fix = Some(Fix::Sift { node: child, order: child_order });
+                    } else {
+                        self.fix = None;
+                    }
+                    return SortResult::Ok;
+                }
+                Some(Fix::Sift { .. }) => self.fix = None,
+                None => {
+                    if let Some(heap) = self.pending.take() {
+                        self.fix = Some(Fix::Trinkle { heap });
+                    } else if self.building && self.next < a.len() {
+                        self.add();
+                        self.fix = Some(Fix::Trinkle { heap: self.orders.len() - 1 });
+                    } else if self.building {
+                        self.building = false;
+                    } else {
+                        // the root of the last heap is the largest, and already where it belongs.
+                        // Taking it off leaves its children as heaps of their own
+                        match self.orders.pop() {
+                            Some(order) if order >= 2 => {
+                                self.orders.extend([order - 1, order - 2]);
+                                let heaps = self.orders.len();
+                                self.fix = Some(Fix::Trinkle { heap: heaps - 2 });
+                                self.pending = Some(heaps - 1);
+                            }
+                            Some(_) => (),
+                            None => return SortResult::Done,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if !self.started {
+            return;
+        }
+        let end = self.heaps().last().map_or(0, |heap| heap.end);
+        annotations.ranges.push(0..if self.building { self.len } else { end });
+        annotations.heaps.extend(self.heaps());
+        annotations.pivots.extend(self.heaps().map(|heap| heap.end - 1));
+
+        let phase = if self.building { "building the forest" } else { "taking the largest" };
+        annotations.stats.push(("phase", phase.to_string()));
+        annotations.stats.push(("heaps", self.orders.len().to_string()));
+    }
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+/// Stooge sort, which swaps the first and last elements if they're out of order, then sorts the
+/// first two thirds, the last two thirds and the first two thirds again. That comes to about
+/// n^2.71 steps, as each third of the recursion makes three calls on two thirds as many elements.
+pub struct StoogeSort {
+    stack: Vec<Range<usize>>, // calls still to make, the next one last
+    current: Option<Range<usize>>,
+    started: bool,
+}
+
+impl StoogeSort {
+    pub fn new() -> StoogeSort {
+        StoogeSort {
+            stack: Vec::new(),
+            current: None,
+            started: false,
+        }
+    }
+}
+
+impl<T> Sort<T> for StoogeSort where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            // each level of the recursion leaves two calls waiting, and the ranges shrink by a
+            // third at each level
+            let (mut len, mut depth) = (a.len(), 0);
+            while len >= 3 {
+                len -= len / 3;
+                depth += 1;
+            }
+            self.stack.reserve(2 * depth + 3);
+            if a.len() >= 2 {
+                self.stack.push(0..a.len());
+            }
+            self.started = true;
+        }
+
+        let Some(range) = self.stack.pop() else {
+            self.current = None;
+            return SortResult::Done;
+        };
+        exchange(a, range.start, range.end - 1, events);
+        let third = range.len() / 3;
+        if third > 0 {
+            let (first, last) = (range.start..range.end - third, range.start + third..range.end);
+            self.stack.extend([first.clone(), last, first]);
+        }
+        self.current = Some(range);
+        SortResult::Ok
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        annotations.ranges.extend(self.current.clone());
+        annotations.stats.push(("calls waiting", self.stack.len().to_string()));
+    }
+}
@@ -0,0 +1,513 @@
+use super::*;
+
+// consecutive wins by one side of a merge that switch it to galloping
+const MIN_GALLOP: usize = 7;
+
+/// Timsort, Python's and Java's sort for objects. It finds the runs already in the array,
+/// reversing descending ones, extends any shorter than a minimum length with binary insertion
+/// sort, and pushes them onto a stack. Runs on the stack are merged whenever their lengths stop
+/// shrinking fast enough, which keeps merges balanced, and a merge that keeps taking from the same
+/// side switches to galloping, skipping ahead with an exponential search.
+pub struct TimSort<T> {
+    min_run: usize,
+    runs: Vec<Range<usize>>, // the merge stack, oldest first
+    next: usize, // where the next run starts
+    buffer: Vec<T>, // the shorter of two runs being merged
+    min_gallop: usize, // grows when galloping doesn't pay off, and shrinks when it does
+    task: Option<Task>,
+    started: bool,
+}
+
+enum Task {
+    Find { start: usize, i: usize, descending: bool }, // comparing `i` with the element before it
+    Reverse { run: Range<usize>, lo: usize, hi: usize }, // swapping `lo` and `hi`, inclusive
+    Insert { run: Range<usize>, end: usize, search: Search }, // finding where `run.end` goes
+    Shift { run: Range<usize>, end: usize, i: usize, pos: usize }, // moving `i` down to `pos`
+    Merge(Merge),
+}
+
+/// Finds how many elements at the front of a sequence satisfy a condition that holds for some
+/// prefix of it, one comparison per step. A galloping search checks offsets 0, 1, 3, 7, ... before
+/// binary searching between the last two, which is quicker when the answer is near the front.
+struct Search {
+    lo: usize,
+    hi: usize,
+    gallop: Option<usize>, // the next offset to check, until the condition fails
+}
+
+impl Search {
+    fn binary(len: usize) -> Search {
+        Search { lo: 0, hi: len, gallop: None }
+    }
+
+    fn gallop(len: usize) -> Search {
+        Search { lo: 0, hi: len, gallop: Some(0) }
+    }
+
+    /// Checks one offset with `holds`, or returns the answer once there's nothing left to check.
+    fn step(&mut self, mut holds: impl FnMut(usize) -> bool) -> Option<usize> {
+        if let Some(offset) = self.gallop.filter(|&offset| offset < self.hi) {
+            if holds(offset) {
+                self.lo = offset + 1;
+                self.gallop = Some(offset * 2 + 1);
+            } else {
+                self.hi = offset;
+                self.gallop = None;
+            }
+            None
+        } else if self.lo < self.hi {
+            let mid = self.lo + (self.hi - self.lo) / 2;
+            if holds(mid) {
+                self.lo = mid + 1;
+            } else {
+                self.hi = mid;
+            }
+            None
+        } else {
+            Some(self.lo)
+        }
+    }
+}
+
+/// A merge of two neighbouring runs. The shorter run is copied into the buffer, and the merge
+/// fills its space from that end: forwards for the left run and backwards for the right.
+struct Merge {
+    range: Range<usize>, // both runs, as they were
+    left: Range<usize>,
+    right: Range<usize>,
+    forward: bool,
+    dest: usize, // the next index written to, or one past it when merging backwards
+    run: Range<usize>, // the part of the other run still to be merged
+    buffered: Range<usize>, // the part of the buffer still to be merged
+    phase: Phase,
+}
+
+enum Phase {
+    // the start of the left run and end of the right that are already in place are left out of
+    // the merge
+    TrimLeft(Search),
+    TrimRight(Search),
+    Copy,
+    OneAtATime { run_wins: usize, buffer_wins: usize },
+    // searching the buffer or the run for where the front of the other one goes. `found` is how
+    // many elements the buffer's search found, in the same round.
+    Gallop { buffer: bool, search: Search, found: usize },
+    // moving `n` more elements found by a search, followed by the one that was searched for
+    Move { buffer: bool, n: usize, count: usize, found: usize },
+    Drain, // the run is used up, so the rest of the buffer goes last
+}
+
+impl Merge {
+    fn new(left: Range<usize>, right: Range<usize>) -> Merge {
+        Merge {
+            range: left.start..right.end,
+            phase: Phase::TrimLeft(Search::gallop(left.len())),
+            forward: true,
+            dest: 0,
+            run: 0..0,
+            buffered: 0..0,
+            left,
+            right,
+        }
+    }
+
+    /// The index of the element `offset` from the front of the run, in the direction of the merge.
+    fn run_front(&self, offset: usize) -> usize {
+        if self.forward { self.run.start + offset } else { self.run.end - 1 - offset }
+    }
+
+    fn buffer_front(&self, offset: usize) -> usize {
+        if self.forward { self.buffered.start + offset } else { self.buffered.end - 1 - offset }
+    }
+
+    fn put<T>(&mut self, a: &mut [T], value: T, events: &mut dyn Events) {
+        if !self.forward {
+            self.dest -= 1;
+        }
+        a[self.dest] = value;
+        events.write(self.dest);
+        if self.forward {
+            self.dest += 1;
+        }
+    }
+
+    fn take_run<T>(&mut self, a: &mut [T], events: &mut dyn Events) where T: Clone {
+        let value = a[self.run_front(0)].clone();
+        if self.forward {
+            self.run.start += 1;
+        } else {
+            self.run.end -= 1;
+        }
+        self.put(a, value, events);
+    }
+
+    fn take_buffer<T>(&mut self, a: &mut [T], buffer: &[T], events: &mut dyn Events) where T: Clone {
+        let value = buffer[self.buffer_front(0)].clone();
+        if self.forward {
+            self.buffered.start += 1;
+        } else {
+            self.buffered.end -= 1;
+        }
+        self.put(a, value, events);
+    }
+
+    /// Returns whether the merge has finished.
+    fn step<T>(
+        &mut self,
+        a: &mut [T],
+        buffer: &mut Vec<T>,
+        min_gallop: &mut usize,
+        events: &mut dyn Events,
+    ) -> bool where T: PartialOrd + Clone {
+        loop {
+            let merging = self.galloping().is_some();
+            if merging && self.buffered.is_empty() {
+                // whatever is left of the run is already in place
+                return true;
+            } else if merging && self.run.is_empty() && !matches!(self.phase, Phase::Drain) {
+                self.phase = Phase::Drain;
+            }
+
+            match &mut self.phase {
+                Phase::TrimLeft(search) => {
+                    let (left, key) = (self.left.start, self.right.start);
+                    let found = search.step(|offset| {
+                        events.compare(left + offset, key);
+                        a[left + offset] <= a[key]
+                    });
+                    let Some(found) = found else {
+                        return false;
+                    };
+                    self.left.start += found;
+                    if self.left.is_empty() {
+                        return true;
+                    }
+                    self.phase = Phase::TrimRight(Search::gallop(self.right.len()));
+                }
+                Phase::TrimRight(search) => {
+                    let (right, key) = (self.right.end, self.left.end - 1);
+                    let found = search.step(|offset| {
+                        events.compare(right - 1 - offset, key);
+                        a[right - 1 - offset] >= a[key]
+                    });
+                    let Some(found) = found else {
+                        return false;
+                    };
+                    self.right.end -= found;
+                    if self.right.is_empty() {
+                        return true;
+                    }
+
+                    self.forward = self.left.len() <= self.right.len();
+                    let (copied, run) = if self.forward {
+                        (self.left.clone(), self.right.clone())
+                    } else {
+                        (self.right.clone(), self.left.clone())
+                    };
+                    self.dest = if self.forward { copied.start } else { copied.end };
+                    self.buffered = 0..copied.len();
+                    self.run = run;
+                    buffer.clear();
+                    self.phase = Phase::Copy;
+                }
+                Phase::Copy if buffer.len() < self.buffered.end => {
+                    let copied = if self.forward { &self.left } else { &self.right };
+                    let i = copied.start + buffer.len();
+                    buffer.push(a[i].clone());
+                    events.read(i);
+                    return false;
+                }
+                Phase::Copy => self.phase = Phase::OneAtATime { run_wins: 0, buffer_wins: 0 },
+                &mut Phase::OneAtATime { run_wins, buffer_wins } => {
+                    let (r, b) = (self.run_front(0), self.buffer_front(0));
+                    events.compare_held(r);
+                    // ties go to the left run, to keep the sort stable
+                    let wins = if self.forward { a[r] < buffer[b] } else { a[r] > buffer[b] };
+                    let (run_wins, buffer_wins) = if wins {
+                        self.take_run(a, events);
+                        (run_wins + 1, 0)
+                    } else {
+                        self.take_buffer(a, buffer, events);
+                        (0, buffer_wins + 1)
+                    };
+
+                    if run_wins.max(buffer_wins) >= *min_gallop {
+                        *min_gallop += 1;
+                        self.start_round(min_gallop);
+                    } else {
+                        self.phase = Phase::OneAtATime { run_wins, buffer_wins };
+                    }
+                    return false;
+                }
+                Phase::Gallop { buffer: true, search, .. } => {
+                    let (forward, buffered) = (self.forward, self.buffered.clone());
+                    let key = if forward { self.run.start } else { self.run.end - 1 };
+                    let found = search.step(|offset| {
+                        events.compare_held(key);
+                        if forward {
+                            buffer[buffered.start + offset] <= a[key]
+                        } else {
+                            buffer[buffered.end - 1 - offset] >= a[key]
+                        }
+                    });
+                    let Some(found) = found else {
+                        return false;
+                    };
+                    self.phase = Phase::Move { buffer: true, n: found, count: found, found: 0 };
+                }
+                Phase::Gallop { buffer: false, search, found } => {
+                    let (forward, run) = (self.forward, self.run.clone());
+                    let key = if forward { self.buffered.start } else { self.buffered.end - 1 };
+                    let count = search.step(|offset| {
+                        if forward {
+                            events.compare_held(run.start + offset);
+                            a[run.start + offset] < buffer[key]
+                        } else {
+                            events.compare_held(run.end - 1 - offset);
+                            a[run.end - 1 - offset] > buffer[key]
+                        }
+                    });
+                    let Some(count) = count else {
+                        return false;
+                    };
+                    self.phase = Phase::Move { buffer: false, n: count, count, found: *found };
+                }
+                Phase::Move { buffer: from_buffer, n, count, found } => {
+                    let (from_buffer, count, found) = (*from_buffer, *count, *found);
+                    let key = *n == 0;
+                    *n = n.saturating_sub(1);
+
+                    // after the elements found, the one they were found for moves too
+                    if from_buffer != key {
+                        self.take_buffer(a, buffer, events);
+                    } else {
+                        self.take_run(a, events);
+                    }
+
+                    if key && from_buffer {
+                        let search = Search::gallop(self.run.len());
+                        self.phase = Phase::Gallop { buffer: false, search, found: count };
+                    } else if key && (found >= MIN_GALLOP || count >= MIN_GALLOP) {
+                        self.start_round(min_gallop);
+                    } else if key {
+                        // galloping stopped paying off, so it's made harder to get back into
+                        *min_gallop += 1;
+                        self.phase = Phase::OneAtATime { run_wins: 0, buffer_wins: 0 };
+                    }
+                    return false;
+                }
+                Phase::Drain => {
+                    self.take_buffer(a, buffer, events);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Starts a round of galloping, which searches the buffer and then the run. Each round makes
+    /// galloping a little easier to get into next time.
+    fn start_round(&mut self, min_gallop: &mut usize) {
+        *min_gallop = (*min_gallop - 1).max(1);
+        let search = Search::gallop(self.buffered.len());
+        self.phase = Phase::Gallop { buffer: true, search, found: 0 };
+    }
+
+    fn galloping(&self) -> Option<bool> {
+        match self.phase {
+            Phase::TrimLeft(_) | Phase::TrimRight(_) | Phase::Copy => None,
+            Phase::OneAtATime { .. } => Some(false),
+            Phase::Gallop { .. } | Phase::Move { .. } | Phase::Drain => Some(true),
+        }
+    }
+}
+
+impl<T> TimSort<T> {
+    pub const PARAMS: [Param; 1] = [
+        Param {
+            name: "Minimum run",
+            kind: Kind::Int { min: 2, max: 64 },
+            default: Value::Int(16),
+        },
+    ];
+
+    pub fn new(min_run: usize) -> TimSort<T> {
+        TimSort {
+            min_run,
+            runs: Vec::new(),
+            next: 0,
+            buffer: Vec::new(),
+            min_gallop: MIN_GALLOP,
+            task: None,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, len: usize) {
+        // the stack invariants make run lengths grow at least as fast as the Fibonacci numbers, so
+        // it never holds more runs than there are bits in the length, plus a couple being merged
+        self.runs.reserve(usize::BITS as usize + 2);
+        self.buffer.reserve(len / 2);
+        self.task = self.find(len);
+        self.started = true;
+    }
+
+    fn find(&self, len: usize) -> Option<Task> {
+        (self.next < len).then(|| Task::Find { start: self.next, i: self.next + 1, descending: false })
+    }
+
+    /// Extends a run that's too short with binary insertion sort, or else pushes it.
+    fn extend(&mut self, run: Range<usize>, len: usize) -> Option<Task> {
+        if run.len() < self.min_run && run.end < len {
+            let end = (run.start + self.min_run).min(len);
+            Some(Task::Insert { search: Search::binary(run.len()), run, end })
+        } else {
+            self.next = run.end;
+            self.runs.push(run);
+            self.collapse(len)
+        }
+    }
+
+    /// Merges the runs on the stack that break its invariants: every run must be longer than the
+    /// two above it put together, and than the one above it. Once the whole array has been found,
+    /// all the runs are merged.
+    fn collapse(&mut self, len: usize) -> Option<Task> {
+        if self.runs.len() < 2 {
+            return self.find(len);
+        }
+
+        let runs = &self.runs;
+        let mut n = runs.len() - 2;
+        if self.next == len {
+            if n > 0 && runs[n - 1].len() < runs[n + 1].len() {
+                n -= 1;
+            }
+        } else if (n > 0 && runs[n - 1].len() <= runs[n].len() + runs[n + 1].len())
+            || (n > 1 && runs[n - 2].len() <= runs[n - 1].len() + runs[n].len())
+        {
+            // the middle run is merged with whichever of its neighbours is shorter
+            if runs[n - 1].len() < runs[n + 1].len() {
+                n -= 1;
+            }
+        } else if runs[n].len() > runs[n + 1].len() {
+            return self.find(len);
+        }
+        Some(Task::Merge(Merge::new(runs[n].clone(), runs[n + 1].clone())))
+    }
+}
+
+impl<T> Sort<T> for TimSort<T> where T: PartialOrd + Clone {
+    fn step(&mut self, a: &mut [T], events: &mut dyn Events) -> SortResult {
+        if !self.started {
+            self.start(a.len());
+        }
+
+        loop {
+            let Some(task) = &mut self.task else {
+                return SortResult::Done;
+            };
+
+            match task {
+                Task::Find { start, i, descending } => {
+                    let compared = *i < a.len();
+                    if compared {
+                        events.compare(*i - 1, *i);
+                        // descending runs have to be strictly descending, so that reversing them
+                        // can't reorder equal elements
+                        let less = a[*i] < a[*i - 1];
+                        if *i == *start + 1 {
+                            *descending = less;
+                        }
+                        if less == *descending {
+                            *i += 1;
+                            return SortResult::Ok;
+                        }
+                    }
+
+                    let run = *start..*i;
+                    self.task = if *descending && run.len() > 1 {
+                        Some(Task::Reverse { lo: run.start, hi: run.end - 1, run })
+                    } else {
+                        self.extend(run, a.len())
+                    };
+                    if compared {
+                        return SortResult::Ok;
+                    }
+                }
+                Task::Reverse { run, lo, hi } => {
+                    if *lo < *hi {
+                        swap(a, *lo, *hi);
+                        events.swap(*lo, *hi);
+                        *lo += 1;
+                        *hi -= 1;
+                        return SortResult::Ok;
+                    }
+                    let run = run.clone();
+                    self.task = self.extend(run, a.len());
+                }
+                Task::Insert { run, end, search } => {
+                    let (start, key) = (run.start, run.end);
+                    let found = search.step(|offset| {
+                        events.compare(start + offset, key);
+                        a[start + offset] <= a[key]
+                    });
+                    match found {
+                        Some(found) => {
+                            let (run, end) = (run.clone(), *end);
+                            self.task = Some(Task::Shift { i: run.end, pos: start + found, run, end });
+                        }
+                        None => return SortResult::Ok,
+                    }
+                }
+                Task::Shift { run, end, i, pos } => {
+                    if *i > *pos {
+                        swap(a, *i - 1, *i);
+                        events.swap(*i - 1, *i);
+                        *i -= 1;
+                        return SortResult::Ok;
+                    }
+                    let run = run.start..run.end + 1;
+                    self.task = if run.end < *end {
+                        Some(Task::Insert { search: Search::binary(run.len()), end: *end, run })
+                    } else {
+                        self.extend(run, a.len())
+                    };
+                }
+                Task::Merge(merge) => {
+                    if !merge.step(a, &mut self.buffer, &mut self.min_gallop, events) {
+                        return SortResult::Ok;
+                    }
+                    let n = self.runs.iter().position(|run| run.start == merge.range.start).unwrap();
+                    self.runs[n].end = self.runs[n + 1].end;
+                    self.runs.remove(n + 1);
+                    self.task = self.collapse(a.len());
+                    return SortResult::Ok;
+                }
+            }
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        let Some(task) = &self.task else {
+            return;
+        };
+
+        annotations.runs.clone_from(&self.runs);
+        let range = match task {
+            Task::Find { start, i, .. } => *start..*i,
+            Task::Reverse { run, .. } => run.clone(),
+            Task::Insert { run, end, .. } | Task::Shift { run, end, .. } => run.start..*end,
+            Task::Merge(merge) => merge.range.clone(),
+        };
+        annotations.ranges.push(range);
+
+        annotations.stats.push(("min gallop", self.min_gallop.to_string()));
+        if let Task::Merge(merge) = task {
+            let mode = match merge.galloping() {
+                None => "finding what's in place",
+                Some(false) => "one at a time",
+                Some(true) => "galloping",
+            };
+            annotations.stats.push(("merging", mode.to_string()));
+        }
+    }
+}
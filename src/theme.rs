@@ -0,0 +1,151 @@
+// Color themes. Every color used by the UI belongs to one of the semantic roles below, so that a
+// theme only has to decide what each role looks like. Bars are drawn with a glyph as well as a
+// color, which lets the monochrome theme tell roles apart by pattern alone.
+
+use ratatui::style::Color;
+
+use crate::sort::{Method, Region};
+
+pub const SOLID: &str = "█";
+
+#[derive(Clone, Copy)]
+pub struct Paint {
+    pub color: Color,
+    pub glyph: &'static str,
+}
+
+const fn solid(color: Color) -> Paint {
+    Paint { color, glyph: SOLID }
+}
+
+pub struct Theme {
+    pub name: &'static str,
+    pub bar: Paint,
+    pub compared: Paint,
+    pub swapped: Paint,
+    pub pivot: Paint,
+    pub sorted: Paint,
+    pub cursor: Paint,
+    pub inactive: Paint, // bars while no sort is running
+    pub methods: [Paint; 3], // ranges sorted by each part of a hybrid sort, by `Method`
+    pub regions: [Paint; 3], // the parts of a dual-pivot partition, by `Region`
+    pub accent: Color, // menu items and headings
+    pub accent_text: Color, // text on an accent background, e.g. the selected menu item
+    pub text: Color,
+    pub background: Color,
+}
+
+pub static THEMES: [Theme; 4] = [
+    Theme {
+        name: "default",
+        bar: solid(Color::White),
+        compared: solid(Color::Cyan),
+        swapped: solid(Color::Red),
+        pivot: solid(Color::Magenta),
+        sorted: solid(Color::Green),
+        cursor: solid(Color::Yellow),
+        inactive: solid(Color::DarkGray),
+        methods: [solid(Color::Blue), solid(Color::Yellow), solid(Color::LightGreen)],
+        regions: [solid(Color::LightBlue), solid(Color::LightYellow), solid(Color::LightRed)],
+        accent: Color::Red,
+        accent_text: Color::Black,
+        text: Color::Reset,
+        background: Color::Reset,
+    },
+    Theme {
+        name: "ocean",
+        bar: solid(Color::Rgb(142, 202, 230)),
+        compared: solid(Color::Rgb(255, 255, 255)),
+        swapped: solid(Color::Rgb(251, 133, 0)),
+        pivot: solid(Color::Rgb(255, 183, 3)),
+        sorted: solid(Color::Rgb(33, 158, 188)),
+        cursor: solid(Color::Rgb(255, 255, 255)),
+        inactive: solid(Color::Rgb(60, 90, 110)),
+        methods: [
+            solid(Color::Rgb(72, 149, 239)),
+            solid(Color::Rgb(239, 71, 111)),
+            solid(Color::Rgb(6, 214, 160)),
+        ],
+        regions: [
+            solid(Color::Rgb(72, 149, 239)),
+            solid(Color::Rgb(255, 230, 160)),
+            solid(Color::Rgb(239, 71, 111)),
+        ],
+        accent: Color::Rgb(255, 183, 3),
+        accent_text: Color::Rgb(2, 48, 71),
+        text: Color::Rgb(220, 235, 245),
+        background: Color::Rgb(2, 48, 71),
+    },
+    // Okabe-Ito palette, distinguishable with all common forms of color blindness
+    Theme {
+        name: "colorblind",
+        bar: solid(Color::Rgb(86, 180, 233)),
+        compared: solid(Color::Rgb(240, 228, 66)),
+        swapped: solid(Color::Rgb(213, 94, 0)),
+        pivot: solid(Color::Rgb(204, 121, 167)),
+        sorted: solid(Color::Rgb(0, 158, 115)),
+        cursor: solid(Color::Rgb(255, 255, 255)),
+        inactive: solid(Color::Rgb(100, 100, 100)),
+        methods: [
+            solid(Color::Rgb(0, 114, 178)),
+            solid(Color::Rgb(230, 159, 0)),
+            solid(Color::Rgb(200, 200, 200)),
+        ],
+        regions: [
+            solid(Color::Rgb(0, 114, 178)),
+            solid(Color::Rgb(200, 200, 200)),
+            solid(Color::Rgb(230, 159, 0)),
+        ],
+        accent: Color::Rgb(230, 159, 0),
+        accent_text: Color::Black,
+        text: Color::Reset,
+        background: Color::Reset,
+    },
+    Theme {
+        name: "monochrome",
+        bar: solid(Color::Reset),
+        compared: Paint { color: Color::Reset, glyph: "▒" },
+        swapped: Paint { color: Color::Reset, glyph: "░" },
+        pivot: Paint { color: Color::Reset, glyph: "▓" },
+        sorted: Paint { color: Color::Reset, glyph: "▚" },
+        cursor: Paint { color: Color::Reset, glyph: "▞" },
+        inactive: Paint { color: Color::Reset, glyph: "▏" },
+        methods: [
+            Paint { color: Color::Reset, glyph: "▌" },
+            Paint { color: Color::Reset, glyph: "▙" },
+            Paint { color: Color::Reset, glyph: "▐" },
+        ],
+        regions: [
+            Paint { color: Color::Reset, glyph: "▌" },
+            Paint { color: Color::Reset, glyph: "▙" },
+            Paint { color: Color::Reset, glyph: "▐" },
+        ],
+        accent: Color::Reset,
+        accent_text: Color::Reset,
+        text: Color::Reset,
+        background: Color::Reset,
+    },
+];
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<&'static Theme> {
+        THEMES.iter().find(|theme| theme.name == name)
+    }
+
+    pub fn method(&self, method: Method) -> Paint {
+        self.methods[method as usize]
+    }
+
+    pub fn region(&self, region: Region) -> Paint {
+        self.regions[region as usize]
+    }
+
+    pub fn cycle(&'static self, forward: bool) -> &'static Theme {
+        let i = THEMES.iter().position(|theme| std::ptr::eq(theme, self)).unwrap_or(0);
+        if forward {
+            &THEMES[(i + 1) % THEMES.len()]
+        } else {
+            &THEMES[(i + THEMES.len() - 1) % THEMES.len()]
+        }
+    }
+}
@@ -1,14 +1,28 @@
+use std::ops::Range;
+
 use ratatui::{
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::Span,
-    widgets::{Bar, BarChart, BarGroup, Block, Borders, List, ListItem, Paragraph},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Padding, Paragraph},
     Frame
 };
 
-use crate::app::{App, AppScreen};
+use crate::{
+    app::{App, AppScreen, Setting, View},
+    keys::{key_label, Action},
+    sort::{Kind, Method, Region, Value},
+    theme::{Paint, Theme, SOLID},
+};
 
 pub fn ui(frame: &mut Frame, app: &App) {
+    let theme = app.theme();
+    frame.render_widget(
+        Block::default().style(Style::default().fg(theme.text).bg(theme.background)),
+        frame.area(),
+    );
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -43,18 +57,40 @@ pub fn ui(frame: &mut Frame, app: &App) {
         render_menu(frame, content_area[0], app);
         render_sort(frame, content_area[1], app);
 
+        match app.current_screen {
+            AppScreen::Sort => render_stats(frame, content_area[1], app),
+            AppScreen::Settings => render_settings(frame, content_area[1], app),
+            AppScreen::Options => render_options(frame, content_area[1], app),
+            AppScreen::Menu => (),
+        }
+
+        if app.help {
+            render_help(frame, content_area[1], app);
+        }
+
     // FOOTER
 
     let footer_block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default());
 
-    let footer = Paragraph::new(
-        match app.current_screen {
-            AppScreen::Menu => "<Enter> to confirm, <Esc> to quit",
-            AppScreen::Sort => "<Esc> to return",
-        }
-    ).alignment(Alignment::Center).block(footer_block);
+    let mut footer_text = Action::footer(app.current_screen).iter()
+        .filter_map(|&action| {
+            let description = action.describe(app.current_screen)?;
+            Some(format!("<{}> {description}", keys_label(app, action)?))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if let Some(message) = &app.message {
+        footer_text += &format!(" | {message}");
+    } else if let Some(i) = app.cursor {
+        footer_text += &format!(" | #{}: {}", i + 1, app.dataset.value(app.data[i]));
+    }
+
+    let footer = Paragraph::new(footer_text)
+        .alignment(Alignment::Center)
+        .block(footer_block);
 
     frame.render_widget(footer, chunks[2]);
     
@@ -63,14 +99,15 @@ pub fn ui(frame: &mut Frame, app: &App) {
 fn render_menu(frame: &mut Frame, area: Rect, app: &App) {
     let mut list_items = vec![];
 
+    let theme = app.theme();
     let (item_style, selected_style) = match app.current_screen {
         AppScreen::Menu => (
-            Style::default().fg(Color::Red),
-            Style::default().bg(Color::Red).fg(Color::Black),
+            Style::default().fg(theme.accent),
+            selected(theme.accent, theme),
         ),
-        AppScreen::Sort => (
-            Style::default().fg(Color::DarkGray),
-            Style::default().bg(Color::DarkGray).fg(Color::Black),
+        AppScreen::Sort | AppScreen::Settings | AppScreen::Options => (
+            Style::default().fg(theme.inactive.color),
+            selected(theme.inactive.color, theme),
         )
     };
 
@@ -95,36 +132,542 @@ fn render_menu(frame: &mut Frame, area: Rect, app: &App) {
 }
 
 fn render_sort(frame: &mut Frame, area: Rect, app: &App) {
-    let mut bars = vec![];
+    let theme = app.theme();
+
+    // bars are measured from zero, or from the smallest value if the data has negatives
+    let values = &app.dataset.values;
+    let low = values[0].min(0.0);
+    let range = values[values.len() - 1].max(0.0) - low;
+    let height = |rank: usize, rows: u16| {
+        let max = (rows as u64 * 8).max(1); // each cell holds 8 levels of bar
+        if range == 0.0 {
+            max
+        } else {
+            1 + ((app.dataset.value(rank) - low) / range * (max - 1) as f64).round() as u64
+        }
+    };
 
-    for i in 0..app.data.len() {
-        let color = if app.current_screen == AppScreen::Menu {
-            Color::DarkGray
+    // sorts that distribute elements into buckets, or count them, get a strip under the chart
+    // showing the buckets or counts. Sorting networks get a bigger one for their comparators
+    let annotations = &app.annotations;
+    let (area, strip) = if annotations.network.is_some() {
+        let [chart, strip] = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .areas(area);
+        (chart, Some(strip))
+    } else if annotations.buckets.is_empty() && annotations.histogram.is_empty() {
+        (area, None)
+    } else {
+        let [chart, strip] = Layout::vertical([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .areas(area);
+        (chart, Some(strip))
+    };
+
+    // sorts that merge runs get a row under the chart bracketing the runs waiting to be merged,
+    // and smoothsort one bracketing the heaps of its forest
+    let spans = if annotations.runs.is_empty() { &annotations.heaps } else { &annotations.runs };
+    let (area, brackets) = if spans.is_empty() || area.height < 3 {
+        (area, None)
+    } else {
+        let [chart, brackets] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)])
+            .areas(area);
+        (chart, Some(brackets))
+    };
+
+    let buffer = frame.buffer_mut();
+
+    // arrays wider than the chart are drawn one column per group of neighbouring bars, sampling
+    // the first bar of each group
+    let columns = app.data.len().min(area.width as usize);
+    for column in 0..columns {
+        let bars = column * app.data.len() / columns..(column + 1) * app.data.len() / columns;
+        let i = bars.start;
+
+        // bars outside the ranges or chain the sort is working on are dimmed, if it reports any
+        let outside = (!annotations.ranges.is_empty()
+            && !annotations.ranges.iter().any(|r| r.start < bars.end && bars.start < r.end))
+            || annotations.chain.is_some_and(|(first, stride)| {
+                // the first element of the chain at or after the start of the group
+                let next = bars.start + (first + stride - bars.start % stride) % stride;
+                next >= bars.end
+            });
+
+        let paint = if app.cursor.is_some_and(|cursor| bars.contains(&cursor)) {
+            theme.cursor
+        } else if app.current_screen != AppScreen::Sort {
+            theme.inactive
         } else if app.n != 0 {
             if i < app.n {
-                Color::Green
+                theme.sorted
             } else {
-                Color::White
+                theme.bar
             }
-        } else{
-            if app.swapped.contains(&app.data[i]) {
-                Color::Red
-            } else {
-                Color::White
+        } else if app.swapped[bars.clone()].contains(&true) {
+            theme.swapped
+        } else if annotations.pivots.iter().any(|pivot| bars.contains(pivot)) {
+            theme.pivot
+        } else if app.compared[bars.clone()].contains(&true) {
+            theme.compared
+        } else if outside {
+            theme.inactive
+        } else if let Some(&(_, method)) = annotations.methods.iter()
+            .find(|(range, _)| range.start < bars.end && bars.start < range.end)
+        {
+            theme.method(method)
+        } else if let Some(&(_, region)) = annotations.regions.iter()
+            .find(|(range, _)| range.start < bars.end && bars.start < range.end)
+        {
+            theme.region(region)
+        } else {
+            theme.bar
+        };
+
+        let x = area.x + column as u16;
+        match app.view() {
+            View::Bars => draw_bar(buffer, x, area, height(app.data[i], area.height), paint),
+            View::Dots => if area.height > 0 {
+                let row = (height(app.data[i], area.height) - 1) / 8; // in cells, counting up from the bottom
+                let symbol = if paint.glyph == SOLID { "•" } else { paint.glyph };
+                buffer[(x, area.bottom() - 1 - row as u16)]
+                    .set_symbol(symbol)
+                    .set_fg(paint.color);
             }
+        }
+    }
+
+    if let Some(row) = brackets {
+        render_brackets(buffer, row, app, spans, !annotations.heaps.is_empty());
+    }
+
+    match strip {
+        Some(strip) if !annotations.buckets.is_empty() => render_buckets(frame, strip, app, height),
+        Some(strip) if annotations.network.is_some() => render_network(frame, strip, app),
+        Some(strip) => render_histogram(frame, strip, app),
+        None => (),
+    }
+}
+
+/// Brackets each span under the bars it covers, labelled with its length if that fits. Spans being
+/// worked on, like runs being merged, are drawn in the accent color. Heaps end in a mark for their
+/// root.
+fn render_brackets(buffer: &mut Buffer, row: Rect, app: &App, spans: &[Range<usize>], heaps: bool) {
+    let theme = app.theme();
+    let len = app.data.len();
+    let columns = len.min(row.width as usize);
+    if columns == 0 {
+        return;
+    }
+    let column = |i: usize| column(i, len, columns);
+
+    let (end, single) = if heaps { ("●", "●") } else { ("┘", "│") };
+    for span in spans {
+        let (first, last) = (column(span.start), column(span.end - 1));
+        // the whole of smoothsort's forest is being worked on, so no heap stands out
+        let active = !heaps
+            && app.annotations.ranges.iter().any(|r| r.start < span.end && span.start < r.end);
+        let style = Style::default().fg(if active { theme.accent } else { theme.text });
+
+        let x = row.x + first as u16;
+        if first == last {
+            buffer.set_string(x, row.y, single, style);
+            continue;
+        }
+        let width = last - first + 1;
+        let mut bracket = format!("└{}{end}", "─".repeat(width - 2));
+        let label = span.len().to_string();
+        if label.len() + 2 <= width - 2 {
+            let at = (width - label.len()) / 2;
+            bracket = bracket.chars().take(at)
+                .chain(label.chars())
+                .chain(bracket.chars().skip(at + label.len()))
+                .collect();
+        }
+        buffer.set_string(x, row.y, bracket, style);
+    }
+}
+
+/// The column drawing bar `i` when `len` bars are drawn in `columns`, which is the last one whose
+/// group starts at or before it. There has to be at least one column.
+fn column(i: usize, len: usize, columns: usize) -> usize {
+    ((i + 1) * columns - 1) / len
+}
+
+/// Draws a sorting network as wires running down from the bars, with each comparator as a line
+/// between the wires it compares. Layers are drawn one after another, starting from the one just
+/// run, which is highlighted, and comparators of a layer that would overlap get rows of their own.
+fn render_network(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let Some(network) = &app.annotations.network else {
+        return;
+    };
+
+    let block = Block::default().borders(Borders::TOP).title(" network ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let len = app.data.len();
+    let columns = len.min(inner.width as usize);
+    if columns == 0 {
+        return;
+    }
+    let buffer = frame.buffer_mut();
+    for x in 0..columns as u16 {
+        for y in inner.top()..inner.bottom() {
+            buffer[(inner.x + x, y)].set_symbol("│").set_fg(theme.inactive.color);
+        }
+    }
+
+    let mut y = inner.y;
+    for (k, layer) in network.layers.iter().enumerate().skip(network.done.saturating_sub(1)) {
+        if y >= inner.bottom() {
+            break;
+        }
+        let style = Style::default().fg(if k + 1 == network.done { theme.accent } else { theme.text });
+
+        // comparators come in order of their first element, so each goes on the first row with
+        // room for it, skipping any that look the same as the one before when bars are grouped
+        let mut ends: Vec<usize> = Vec::new(); // the column after the last one used on each row
+        let mut last = None;
+        for &(i, j) in layer {
+            let (first, second) = (column(i, len, columns), column(j, len, columns));
+            if last == Some((first, second)) {
+                continue;
+            }
+            last = Some((first, second));
+            let row = match ends.iter().position(|&end| end <= first) {
+                Some(row) => row,
+                None => {
+                    ends.push(0);
+                    ends.len() - 1
+                }
+            };
+            ends[row] = second + 1;
+
+            let row = y + row as u16;
+            if row < inner.bottom() {
+                let comparator = match second - first {
+                    0 => "●".to_string(),
+                    width => format!("●{}●", "─".repeat(width - 1)),
+                };
+                buffer.set_string(inner.x + first as u16, row, comparator, style);
+            }
+        }
+        y += ends.len() as u16 + 1; // with a row of bare wires between layers
+    }
+}
+
+/// Draws the contents of each bucket side by side, above its digit.
+fn render_buckets(frame: &mut Frame, area: Rect, app: &App, height: impl Fn(usize, u16) -> u64) {
+    let theme = app.theme();
+    let buckets = &app.annotations.buckets;
+
+    let block = Block::default().borders(Borders::TOP).title(" buckets ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if inner.height < 2 || (inner.width as usize) < buckets.len() {
+        return;
+    }
+
+    let bars_area = Rect { height: inner.height - 1, ..inner };
+    let width = inner.width / buckets.len() as u16; // including a column of space between buckets
+    let buffer = frame.buffer_mut();
+
+    for (digit, bucket) in buckets.iter().enumerate() {
+        let x = inner.x + digit as u16 * width;
+        let columns = bucket.len().min(width.saturating_sub(1).max(1) as usize);
+        for column in 0..columns {
+            let rank = bucket[column * bucket.len() / columns];
+            draw_bar(buffer, x + column as u16, bars_area, height(rank, bars_area.height), theme.bar);
+        }
+
+        let label = digit.to_string();
+        if label.len() < width as usize {
+            buffer.set_string(x, inner.bottom() - 1, label, Style::default().fg(theme.accent));
+        }
+    }
+}
+
+/// Draws the counts as bars, scaled to the largest. A few counts are drawn as wide bars; too many to
+/// fit are drawn one column per group of neighbouring counts, showing the largest of each group.
+fn render_histogram(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let histogram = &app.annotations.histogram;
+
+    let block = Block::default().borders(Borders::TOP).title(" counts ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let max = histogram.iter().copied().max().unwrap_or(0).max(1) as u64;
+    let levels = inner.height as u64 * 8;
+    let groups = histogram.len().min(inner.width as usize);
+    let width = inner.width as usize / groups.max(1);
+    let buffer = frame.buffer_mut();
+
+    for group in 0..groups {
+        let counts = group * histogram.len() / groups..(group + 1) * histogram.len() / groups;
+        let count = histogram[counts].iter().copied().max().unwrap_or(0) as u64;
+        // wide bars are separated by a column of space
+        let columns = if width > 2 { width - 1 } else { width };
+        for column in 0..columns {
+            let x = inner.x + (group * width + column) as u16;
+            draw_bar(buffer, x, inner, (count * levels).div_ceil(max), theme.bar);
+        }
+    }
+}
+
+/// Draws a bar `height` eighths of a cell tall, up from the bottom of `area`. Solid bars use
+/// partial blocks for their top cell; patterned ones are rounded up to whole cells.
+fn draw_bar(buffer: &mut Buffer, x: u16, area: Rect, height: u64, paint: Paint) {
+    const PARTIAL: [&str; 8] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇"];
+
+    for row in 0..area.height {
+        let filled = height.saturating_sub(row as u64 * 8).min(8) as usize;
+        let symbol = match filled {
+            0 => break,
+            8 => paint.glyph,
+            _ if paint.glyph == SOLID => PARTIAL[filled],
+            _ => paint.glyph,
         };
+        buffer[(x, area.bottom() - 1 - row)]
+            .set_symbol(symbol)
+            .set_fg(paint.color);
+    }
+}
 
-        bars.push(
-            Bar::default()
-                .value(app.data[i].try_into().unwrap())
-                .style(color)
-                .value_style(Style::new().bg(color).fg(color))
-        );
+fn render_stats(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let input = if app.custom {
+        "custom"
+    } else {
+        app.distribution().name()
+    };
+
+    let mut lines = vec![
+        Line::from(algorithm_label(app)).style(Style::default().fg(theme.accent)),
+        Line::from(format!("n: {} ({input})", app.data.len())),
+        Line::from(format!("steps: {}", app.stats.steps)),
+        Line::from(format!("comparisons: {}", app.stats.comparisons)),
+        Line::from(format!("writes: {}", app.stats.writes)),
+        Line::from(format!("time: {:.1}s", app.stats.elapsed().as_secs_f64())),
+        Line::from(format!("speed: {} steps/s", app.speed())),
+    ];
+    for (name, value) in &app.annotations.stats {
+        lines.push(Line::from(format!("{name}: {value}")));
+    }
+    // sorts that give up are the ones that could run for longer than anyone would wait, so they
+    // get an estimate of how long that is
+    if let Some((taken, limit)) = app.annotations.budget {
+        let seconds = app.expected_steps / app.speed() as f64;
+        lines.push(Line::from(format!("budget: {taken} of {limit} steps")));
+        lines.push(Line::from(format!("expected: ~{} steps", estimate(app.expected_steps))));
+        lines.push(Line::from(format!("expected time: ~{}", duration_estimate(seconds))));
+    }
+    // hybrid sorts get a key to the colors of the algorithms they switch between, with the one
+    // currently running underlined
+    if !app.annotations.methods.is_empty() {
+        lines.push(color_key(Method::ALL.map(|method| {
+            let running = app.annotations.methods.iter().any(|&(_, m)| m == method);
+            (theme.method(method), method.name(), running)
+        })));
+    }
+    if !app.annotations.regions.is_empty() {
+        lines.push(color_key(Region::ALL.map(|region| (theme.region(region), region.name(), false))));
+    }
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let stats_area = Rect::new(area.x + 1, area.y, width, height).intersection(area);
+
+    let mut block = Block::default().borders(Borders::ALL).padding(Padding::horizontal(1));
+    if app.paused {
+        block = block.title(" paused ");
     }
+    let stats = Paragraph::new(lines).block(block);
 
-    let bar_chart = BarChart::default()
-        .bar_gap(0)
-        .data(BarGroup::default().bars(&bars));
+    frame.render_widget(Clear, stats_area);
+    frame.render_widget(stats, stats_area);
+}
+
+/// A rough number, in scientific notation once it's too long to read.
+fn estimate(n: f64) -> String {
+    if n.is_infinite() {
+        format!("more than {:.0e}", f64::MAX)
+    } else if n < 1e6 {
+        format!("{n:.0}")
+    } else {
+        format!("{n:.1e}")
+    }
+}
+
+/// A rough length of time, in whatever unit keeps the number small.
+fn duration_estimate(seconds: f64) -> String {
+    const UNITS: [(&str, f64); 4] = [
+        ("minutes", 60.0),
+        ("hours", 60.0 * 60.0),
+        ("days", 24.0 * 60.0 * 60.0),
+        ("years", 365.25 * 24.0 * 60.0 * 60.0),
+    ];
+    let mut duration = format!("{seconds:.0} seconds");
+    for (unit, length) in UNITS {
+        if seconds >= length {
+            duration = format!("{} {unit}", estimate(seconds / length));
+        }
+    }
+    duration
+}
 
-    frame.render_widget(bar_chart, area);
+/// A line of colored glyphs, each followed by what it stands for, which is underlined if it's
+/// highlighted.
+fn color_key<const N: usize>(entries: [(Paint, &'static str, bool); N]) -> Line<'static> {
+    let spans = entries.into_iter().enumerate().flat_map(|(i, (paint, name, highlighted))| {
+        let mut style = Style::default();
+        if highlighted {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        [
+            Span::raw(if i == 0 { "" } else { " " }),
+            Span::styled(paint.glyph, Style::default().fg(paint.color)),
+            Span::styled(format!(" {name}"), style),
+        ]
+    });
+    Line::from(spans.collect::<Vec<_>>())
+}
+
+fn render_settings(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let lines: Vec<Line> = Setting::ALL.iter().enumerate()
+        .map(|(i, &setting)| {
+            let line = Line::from(format!("{:<20}{}", setting.name(), app.setting_value(setting)));
+            if i == app.setting {
+                line.style(selected(theme.accent, theme))
+            } else {
+                line.style(Style::default().fg(theme.accent))
+            }
+        })
+        .collect();
+
+    let settings_area = centered(area, 48, lines.len() as u16 + 2);
+
+    let settings = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Settings ")
+            .padding(Padding::horizontal(1))
+    );
+
+    frame.render_widget(Clear, settings_area);
+    frame.render_widget(settings, settings_area);
+}
+
+fn render_options(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let algorithm = &app.algorithms[app.selected];
+    let lines: Vec<Line> = algorithm.params.iter().enumerate()
+        .map(|(i, param)| {
+            let value = param.format(app.params[app.selected][i]);
+            let range = match param.kind {
+                Kind::Int { min, max } | Kind::Tenths { min, max } => format!(
+                    " ({}-{})",
+                    param.format(Value::Int(min)),
+                    param.format(Value::Int(max)),
+                ),
+                Kind::Choice(_) => String::new(),
+            };
+            let line = Line::from(format!("{:<20}{value}{range}", param.name));
+            if i == app.param {
+                line.style(selected(theme.accent, theme))
+            } else {
+                line.style(Style::default().fg(theme.accent))
+            }
+        })
+        .collect();
+
+    let options_area = centered(area, 48, lines.len() as u16 + 2);
+
+    let options = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", algorithm.name))
+            .padding(Padding::horizontal(1))
+    );
+
+    frame.render_widget(Clear, options_area);
+    frame.render_widget(options, options_area);
+}
+
+/// The selected algorithm's name followed by its chosen options, e.g.
+/// "Quicksort (Hoare, middle, insertion cutoff 8)".
+fn algorithm_label(app: &App) -> String {
+    let algorithm = &app.algorithms[app.selected];
+    if algorithm.params.is_empty() {
+        return algorithm.name.to_string();
+    }
+    let choices: Vec<_> = algorithm.params.iter()
+        .zip(&app.params[app.selected])
+        .map(|(param, &value)| match param.kind {
+            Kind::Choice(_) => param.format(value),
+            Kind::Int { .. } | Kind::Tenths { .. } => {
+                format!("{} {}", param.name.to_lowercase(), param.format(value))
+            }
+        })
+        .collect();
+    // names that already end in parentheses, like "Radix sort (LSD)", share them
+    match algorithm.name.strip_suffix(')') {
+        Some(name) => format!("{name}, {})", choices.join(", ")),
+        None => format!("{} ({})", algorithm.name, choices.join(", ")),
+    }
+}
+
+/// Text on a background of `color`. The colors are applied reversed so that themes without colors
+/// still get a visible highlight.
+fn selected(color: Color, theme: &Theme) -> Style {
+    Style::default()
+        .fg(color)
+        .bg(theme.accent_text)
+        .add_modifier(Modifier::REVERSED)
+}
+
+fn render_help(frame: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme();
+    let lines: Vec<Line> = Action::ALL.iter()
+        .filter_map(|&action| {
+            let description = action.describe(app.current_screen)?;
+            let keys = keys_label(app, action)?;
+            Some(Line::from(vec![
+                Span::styled(format!("{keys:<12}"), Style::default().fg(theme.accent)),
+                Span::raw(description),
+            ]))
+        })
+        .collect();
+
+    let help_area = centered(area, 48, lines.len() as u16 + 2);
+
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Keys ")
+            .padding(Padding::horizontal(1))
+    );
+
+    frame.render_widget(Clear, help_area);
+    frame.render_widget(help, help_area);
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// All keys bound to `action`, e.g. "Esc/q", or `None` if it's unbound.
+fn keys_label(app: &App, action: Action) -> Option<String> {
+    let keys = app.config.keys.keys(action);
+    if keys.is_empty() {
+        return None;
+    }
+    Some(keys.iter().map(|&key| key_label(key)).collect::<Vec<_>>().join("/"))
 }
\ No newline at end of file
@@ -0,0 +1,263 @@
+// Runs a sort on a background thread, so that an expensive step can't hold up drawing or input.
+// The UI grants the worker a number of steps each frame; the worker runs them on its own copy of
+// the data and sends back a `Batch` with the result. Only one batch can be waiting at a time, so
+// a worker that gets ahead of the UI blocks until the previous batch has been picked up.
+
+use std::{
+    mem,
+    iter,
+    ops::Range,
+    sync::mpsc::{self, Receiver, Sender, SyncSender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::rngs::StdRng;
+
+use crate::sort::{Annotations, Events, Sort, SortResult, Value};
+
+// a batch is sent at least this often while there are steps to run, so that huge step counts
+// can't starve the UI of updates
+const BATCH_TIME: Duration = Duration::from_millis(12);
+
+pub enum Command {
+    Run(u64), // run up to this many more steps; ignored while paused
+    Step, // run a single step, even while paused
+    Pause,
+    Resume,
+    Stop,
+}
+
+pub struct Batch {
+    pub data: Vec<usize>,
+    pub swapped: Vec<bool>, // indices swapped during the batch
+    pub compared: Vec<bool>, // indices compared during the batch
+    pub comparisons: u64,
+    pub writes: u64, // elements written to the array, two for each swap
+    pub annotations: Annotations,
+    pub steps: u64,
+    pub done: bool,
+    pub abandoned: bool, // the sort gave up, so it's done without the array being sorted
+}
+
+pub struct Worker {
+    commands: Sender<Command>,
+    batches: Receiver<Batch>,
+}
+
+impl Worker {
+    /// Starts sorting `data` with the sort created by `new` from `params` and `rng`. The sort is
+    /// created on the worker thread, so it doesn't need to be `Send`.
+    pub fn spawn(
+        new: fn(&[Value], StdRng) -> Box<dyn Sort<usize>>,
+        params: Vec<Value>,
+        rng: StdRng,
+        data: Vec<usize>,
+    ) -> Worker {
+        let (commands, command_receiver) = mpsc::channel();
+        let (batch_sender, batches) = mpsc::sync_channel(1);
+
+        thread::spawn(move || run(new(&params, rng), data, command_receiver, batch_sender));
+
+        Worker { commands, batches }
+    }
+
+    pub fn send(&self, command: Command) {
+        // an error just means the worker has finished
+        let _ = self.commands.send(command);
+    }
+
+    /// Returns the next finished batch, if there is one.
+    pub fn poll(&self) -> Option<Batch> {
+        self.batches.try_recv().ok()
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // the thread isn't joined, as it may be in the middle of a long step; it exits when it
+        // next checks for commands or tries to send a batch
+        self.send(Command::Stop);
+    }
+}
+
+/// Marks the indices touched by a batch of steps, and counts the comparisons and writes.
+struct Marks {
+    swapped: Vec<bool>,
+    compared: Vec<bool>,
+    comparisons: u64,
+    writes: u64,
+}
+
+impl Marks {
+    fn new(len: usize) -> Marks {
+        Marks {
+            swapped: vec![false; len],
+            compared: vec![false; len],
+            comparisons: 0,
+            writes: 0,
+        }
+    }
+}
+
+impl Events for Marks {
+    fn swap(&mut self, i: usize, j: usize) {
+        self.swapped[i] = true;
+        self.swapped[j] = true;
+        self.writes += 2;
+    }
+
+    fn compare(&mut self, i: usize, j: usize) {
+        self.compared[i] = true;
+        self.compared[j] = true;
+        self.comparisons += 1;
+    }
+
+    fn compare_held(&mut self, i: usize) {
+        self.compared[i] = true;
+        self.comparisons += 1;
+    }
+
+    fn read(&mut self, i: usize) {
+        self.compared[i] = true;
+    }
+
+    // overwritten elements are shown the same as swapped ones, as having changed
+    fn write(&mut self, i: usize) {
+        self.swapped[i] = true;
+        self.writes += 1;
+    }
+
+    // every element of a reversed range is marked, including an unmoved middle one, so that the
+    // whole flip shows up as one operation
+    fn reverse(&mut self, range: Range<usize>) {
+        self.writes += range.len() as u64 / 2 * 2;
+        self.swapped[range].fill(true);
+    }
+
+    fn rotate(&mut self, range: Range<usize>, mid: usize) {
+        if mid != range.start && mid != range.end {
+            self.writes += range.len() as u64;
+        }
+        self.swapped[range].fill(true);
+    }
+}
+
+fn run(
+    mut sort: Box<dyn Sort<usize>>,
+    mut data: Vec<usize>,
+    commands: Receiver<Command>,
+    batches: SyncSender<Batch>,
+) {
+    let mut paused = false;
+    let mut marks = Marks::new(data.len());
+
+    loop {
+        // wait for something to do, then take any other commands that queued up in the meantime
+        let Ok(command) = commands.recv() else {
+            return;
+        };
+        let mut steps = 0;
+        for command in iter::once(command).chain(commands.try_iter()) {
+            match command {
+                Command::Run(n) if !paused => steps += n,
+                Command::Run(_) => (),
+                Command::Step => steps += 1,
+                Command::Pause => {
+                    paused = true;
+                    steps = 0;
+                }
+                Command::Resume => paused = false,
+                Command::Stop => return,
+            }
+        }
+        if steps == 0 {
+            continue;
+        }
+
+        let started = Instant::now();
+        let mut batch_steps = 0;
+        let (mut done, mut abandoned) = (false, false);
+        while batch_steps < steps {
+            batch_steps += 1;
+            match sort.step(&mut data, &mut marks) {
+                SortResult::Done => done = true,
+                SortResult::Abandoned => (done, abandoned) = (true, true),
+                SortResult::Ok => (),
+            }
+            if done {
+                break;
+            }
+
+            // checking the clock every step would slow down fast sorts noticeably. Steps that
+            // don't fit in the batch are dropped rather than letting the worker fall further and
+            // further behind.
+            if batch_steps % 1024 == 0 && started.elapsed() > BATCH_TIME {
+                break;
+            }
+        }
+
+        let marks = mem::replace(&mut marks, Marks::new(data.len()));
+        let mut annotations = Annotations::default();
+        sort.annotate(&mut annotations);
+        let batch = Batch {
+            data: data.clone(),
+            swapped: marks.swapped,
+            compared: marks.compared,
+            comparisons: marks.comparisons,
+            writes: marks.writes,
+            annotations,
+            steps: batch_steps,
+            done,
+            abandoned,
+        };
+        if batches.send(batch).is_err() || done {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::sort::{shuffle, InsertionSort};
+
+    fn spawn() -> Worker {
+        let mut data: Vec<usize> = (1..=50).collect();
+        shuffle(&mut data, &mut rand::thread_rng());
+        let rng = StdRng::from_entropy();
+        Worker::spawn(|_, _| Box::new(InsertionSort::new()), Vec::new(), rng, data)
+    }
+
+    #[test]
+    fn sorts() {
+        let worker = spawn();
+        loop {
+            worker.send(Command::Run(100));
+            let batch = worker.batches.recv_timeout(Duration::from_secs(5)).unwrap();
+            if batch.done {
+                assert!(batch.data.windows(2).all(|w| w[0] <= w[1]));
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn pause() {
+        let worker = spawn();
+        worker.send(Command::Pause);
+        worker.send(Command::Run(100));
+        assert!(worker.batches.recv_timeout(Duration::from_millis(100)).is_err());
+
+        worker.send(Command::Step);
+        let batch = worker.batches.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(batch.steps, 1);
+
+        worker.send(Command::Resume);
+        worker.send(Command::Run(100));
+        let batch = worker.batches.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(batch.steps, 100);
+    }
+}